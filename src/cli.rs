@@ -0,0 +1,514 @@
+use simple_time_tracker::database::{self, SqliteStorage};
+use simple_time_tracker::model::{self, format_duration};
+use simple_time_tracker::{csv, ics, import, json, timeclock, timewarrior, toggl};
+use simple_time_tracker::{
+    Dataset, DateRange, ImportMode, OperationError, Storage, SystemClock, Target, Tracker,
+};
+use std::io::Read;
+use std::rc::Rc;
+
+use crate::DatabaseLocation;
+
+const USAGE: &str = "Usage: simple_time_tracker [--db <path>] [--timer <name>] <command>
+
+Without a command the graphical interface is started.
+Commands use the timer given with --timer, by default the first one.
+
+Commands:
+    start                         Start the timer
+    pause                         Pause the timer
+    clear                         Reset the timer to zero
+    status                        Show all timers
+    new-timer <name>              Add a paused timer
+    remove-timer <name>           Remove a timer and discard its time
+    box <duration>                Set a time box the timer counts down towards, off removes it
+    add <duration> <description>  Split time off the timer into a new entry
+    add-to <entry> <duration>     Split time off the timer into an existing entry
+    subtract <entry> <duration>   Remove time from an entry
+    move <from> <to> <duration>   Move time from one entry to another
+    return <entry> <duration>     Move time from an entry back onto the timer
+    list                          List the tracked times with their numbers and IDs
+    delete <entry>                Delete an entry
+    export-csv [<file>]           Write the tracked times as CSV, to standard output without a file
+        --columns <names>         Comma separated columns out of index, id, date, description,
+                                  project, client, tags, duration, hours and seconds
+        --from <date>             Only entries on or after the date, given as YYYY-MM-DD
+        --to <date>               Only entries on or before the date
+    export-json [<file>]          Write everything that is stored as JSON
+    import-json <file>            Add the entries of a JSON export that are not stored yet
+        --replace                 Replace everything that is stored instead
+    import-toggl <file>           Add the entries of a Toggl Track detailed report CSV
+        --dry-run                 Only list what an import would add
+    export-timewarrior [<file>]   Write the sessions as a Timewarrior data file
+    import-timewarrior <file>     Add the intervals of a Timewarrior data file
+    export-timeclock [<file>]     Write the sessions as a ledger timeclock file
+    import-timeclock <file>       Add the sessions of a ledger timeclock file
+    export-ics [<file>]           Write the sessions as calendar events in an iCalendar file
+        --from, --to              Only sessions of entries in the date range, like for export-csv
+
+An entry is given by its number in the list (3), its ID (@12) or a part of its description
+that matches only one entry (standup). Numbers change when entries are deleted, IDs do not.
+
+A duration is given as minutes (90), H:MM or H:MM:SS (1:30), with units (1h30m, 1.5h, 45s),
+as a percentage (50%), as all of the time (all) or relative to it (all-10m).
+Durations refer to the timer when adding and to the entry the time is taken from otherwise.
+
+Files given as - are standard input or output. Replacing imports back up the database next to it first.";
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<OperationError> for CliError {
+    fn from(error: OperationError) -> Self {
+        CliError::Failed(error.to_string())
+    }
+}
+
+impl From<database::Error> for CliError {
+    fn from(error: database::Error) -> Self {
+        CliError::Failed(error.to_string())
+    }
+}
+
+// Returns the process exit code
+pub fn run(location: &DatabaseLocation, args: &[String]) -> i32 {
+    let (timer_name, args) = split_option(args, "--timer");
+    let result = if location.adopt_legacy {
+        database::adopt_legacy_database(&location.path).map_err(CliError::from)
+    } else {
+        Ok(())
+    }
+    .and_then(|_| {
+        run_command(
+            &SqliteStorage::new(location.path.clone()),
+            timer_name.as_deref(),
+            &args,
+        )
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            2
+        }
+        Err(CliError::Failed(message)) => {
+            eprintln!("Error: {}", message);
+            1
+        }
+    }
+}
+
+// Splits off an option like "--timer <name>", which may be given anywhere like "--db"
+fn split_option(args: &[String], option: &str) -> (Option<String>, Vec<String>) {
+    let prefix = format!("{}=", option);
+    let mut value = None;
+    let mut remaining = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == option {
+            value = args.next().cloned();
+        } else if let Some(option_value) = arg.strip_prefix(&prefix) {
+            value = Some(option_value.to_string());
+        } else {
+            remaining.push(arg.clone());
+        }
+    }
+    (value, remaining)
+}
+
+fn run_command(
+    storage: &SqliteStorage,
+    timer_name: Option<&str>,
+    args: &[String],
+) -> Result<(), CliError> {
+    if args.is_empty() {
+        return Err(CliError::Usage(String::from("Missing command")));
+    }
+    let command = args[0].as_str();
+    let args = &args[1..];
+    match (command, args.len()) {
+        ("help", _) | ("--help", _) | ("-h", _) => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        ("start", 0) => set_running(storage, timer_name, true),
+        ("pause", 0) => set_running(storage, timer_name, false),
+        ("clear", 0) => change_timers(storage, |tracker| {
+            let timer = select_timer(tracker, timer_name)?;
+            tracker.clear(timer);
+            Ok(())
+        }),
+        ("status", 0) => {
+            print_timers(&Tracker::load(storage, Rc::new(SystemClock))?);
+            Ok(())
+        }
+        ("new-timer", 1) => {
+            change_timers(storage, |tracker| tracker.add_timer(&args[0]).map(|_| ()))
+        }
+        ("remove-timer", 1) => change_timers(storage, |tracker| {
+            let timer = tracker.find_timer(&args[0])?;
+            tracker.remove_timer(timer).map(|_| ())
+        }),
+        ("box", 1) => change_timers(storage, |tracker| {
+            let timer = select_timer(tracker, timer_name)?;
+            tracker.set_time_box(timer, &args[0])
+        }),
+        ("add", n) if n >= 2 => {
+            let description = args[1..].join(" ");
+            let target = Target::New {
+                description,
+                project_id: None,
+            };
+            change_entries(storage, |tracker| {
+                let timer = select_timer(tracker, timer_name)?;
+                tracker
+                    .apply_operation(timer, &args[0], target)
+                    .map(|index| vec![index])
+            })
+        }
+        ("add-to", 2) => change_entries(storage, |tracker| {
+            let timer = select_timer(tracker, timer_name)?;
+            let target = Target::Existing(tracker.find_entry(&args[0])?);
+            tracker
+                .apply_operation(timer, &args[1], target)
+                .map(|index| vec![index])
+        }),
+        ("subtract", 2) => change_entries(storage, |tracker| {
+            let index = tracker.find_entry(&args[0])?;
+            tracker.subtract(index, &args[1]).map(|index| vec![index])
+        }),
+        ("move", 3) => change_entries(storage, |tracker| {
+            let from = tracker.find_entry(&args[0])?;
+            let to = tracker.find_entry(&args[1])?;
+            tracker
+                .transfer(from, to, &args[2])
+                .map(|(from, to)| vec![from, to])
+        }),
+        ("return", 2) => change_entries(storage, |tracker| {
+            let timer = select_timer(tracker, timer_name)?;
+            let index = tracker.find_entry(&args[0])?;
+            tracker
+                .return_to_timer(index, timer, &args[1])
+                .map(|index| vec![index])
+        }),
+        ("list", 0) => {
+            let tracker = Tracker::load(storage, Rc::new(SystemClock))?;
+            for (i, tracked_time) in tracker.tracked_times.iter().enumerate() {
+                let project = tracked_time
+                    .project_id
+                    .and_then(|id| tracker.project(id))
+                    .map(|project| format!(" [{}]", project.name))
+                    .unwrap_or_default();
+                let tags = tracked_time
+                    .tags
+                    .iter()
+                    .map(|tag| format!(" #{}", tag))
+                    .collect::<String>();
+                let id = tracked_time
+                    .id
+                    .map(|id| format!("@{}", id))
+                    .unwrap_or_default();
+                let planned = tracked_time
+                    .remaining()
+                    .map(|remaining| format!(" ({})", format_time_box(remaining)))
+                    .unwrap_or_default();
+                println!(
+                    "{:>3}  {:<5}  {}  {:>6}{}{}  {}{}",
+                    i + 1,
+                    id,
+                    tracked_time.date,
+                    format_duration(tracked_time.duration()),
+                    planned,
+                    project,
+                    tracked_time.description,
+                    tags
+                );
+            }
+            Ok(())
+        }
+        ("delete", 1) => {
+            let tracker = Tracker::load(storage, Rc::new(SystemClock))?;
+            let index = tracker.find_entry(&args[0])?;
+            let tracked_time = &tracker.tracked_times[index - 1];
+            storage.delete_tracked_time(tracked_time)?;
+            println!("Deleted {}: {}", index, tracked_time.description);
+            Ok(())
+        }
+        ("export-csv", _) => {
+            let (columns, args) = split_option(args, "--columns");
+            let (range, args) = split_date_range(&args)?;
+            if args.len() > 1 {
+                return Err(CliError::Usage(String::from(
+                    "Wrong number of arguments for 'export-csv'",
+                )));
+            }
+            let columns = match columns {
+                Some(columns) => parse_columns(&columns)?,
+                None => csv::DEFAULT_COLUMNS.to_vec(),
+            };
+            let tracker = Tracker::load(storage, Rc::new(SystemClock))?;
+            write_output(args.first(), &csv::export(&tracker, &columns, &range))
+        }
+        ("export-json", n) if n <= 1 => {
+            let dataset = Dataset::load(storage, &SystemClock)?;
+            write_output(args.first(), &json::export(&dataset, &SystemClock))
+        }
+        ("import-json", _) => {
+            let (replace, args) = split_flag(args, "--replace");
+            let mode = match replace {
+                true => ImportMode::Replace,
+                false => ImportMode::Merge,
+            };
+            run_import(storage, command, &args, mode, |input| {
+                json::import(input, &SystemClock).map_err(|error| error.to_string())
+            })
+        }
+        ("import-toggl", _) => run_import(storage, command, args, ImportMode::Merge, |input| {
+            toggl::import(input).map_err(|error| error.to_string())
+        }),
+        ("export-timewarrior", _) | ("export-timeclock", _) | ("export-ics", _) => {
+            let (range, args) = split_date_range(args)?;
+            if args.len() > 1 {
+                return Err(CliError::Usage(format!(
+                    "Wrong number of arguments for '{}'",
+                    command
+                )));
+            }
+            let tracker = Tracker::load(storage, Rc::new(SystemClock))?;
+            let content = match command {
+                "export-timewarrior" => timewarrior::export(&tracker, &range),
+                "export-ics" => ics::export(&tracker, &range, &SystemClock),
+                _ => timeclock::export(&tracker, &range),
+            };
+            write_output(args.first(), &content)
+        }
+        ("import-timewarrior", _) => {
+            run_import(storage, command, args, ImportMode::Merge, |input| {
+                timewarrior::import(input).map_err(|error| error.to_string())
+            })
+        }
+        ("import-timeclock", _) => run_import(storage, command, args, ImportMode::Merge, |input| {
+            timeclock::import(input).map_err(|error| error.to_string())
+        }),
+        ("start", _)
+        | ("pause", _)
+        | ("clear", _)
+        | ("status", _)
+        | ("new-timer", _)
+        | ("remove-timer", _)
+        | ("box", _)
+        | ("add", _)
+        | ("add-to", _)
+        | ("subtract", _)
+        | ("move", _)
+        | ("return", _)
+        | ("list", _)
+        | ("delete", _)
+        | ("export-json", _) => Err(CliError::Usage(format!(
+            "Wrong number of arguments for '{}'",
+            command
+        ))),
+        _ => Err(CliError::Usage(format!("Unknown command '{}'", command))),
+    }
+}
+
+fn parse_columns(input: &str) -> Result<Vec<csv::Column>, CliError> {
+    input
+        .split(',')
+        .map(|name| {
+            csv::Column::parse(name)
+                .ok_or_else(|| CliError::Usage(format!("Unknown column '{}'", name.trim())))
+        })
+        .collect()
+}
+
+// Splits off a flag without a value, returns whether it was given
+fn split_flag(args: &[String], flag: &str) -> (bool, Vec<String>) {
+    let remaining = args
+        .iter()
+        .filter(|arg| *arg != flag)
+        .cloned()
+        .collect::<Vec<String>>();
+    (remaining.len() < args.len(), remaining)
+}
+
+// Splits off "--from <date>" and "--to <date>"
+fn split_date_range(args: &[String]) -> Result<(DateRange, Vec<String>), CliError> {
+    let (from, args) = split_option(args, "--from");
+    let (to, args) = split_option(&args, "--to");
+    let parse = |input: Option<String>| match input {
+        Some(input) => model::parse_date(&input)
+            .map(Some)
+            .ok_or_else(|| CliError::Usage(format!("Invalid date '{}', use YYYY-MM-DD", input))),
+        None => Ok(None),
+    };
+    let range = DateRange {
+        from: parse(from)?,
+        to: parse(to)?,
+    };
+    Ok((range, args))
+}
+
+// Writes to standard output without a file or with "-"
+fn write_output(path: Option<&String>, content: &str) -> Result<(), CliError> {
+    match path.map(String::as_str) {
+        None | Some("-") => {
+            print!("{}", content);
+            Ok(())
+        }
+        Some(path) => std::fs::write(path, content)
+            .map_err(|error| CliError::Failed(format!("Could not write {}: {}", path, error))),
+    }
+}
+
+// Takes the file and "--dry-run", entries that were imported before are left out
+fn run_import(
+    storage: &SqliteStorage,
+    command: &str,
+    args: &[String],
+    mode: ImportMode,
+    read: impl FnOnce(&str) -> Result<Dataset, String>,
+) -> Result<(), CliError> {
+    let (dry_run, args) = split_flag(args, "--dry-run");
+    if args.len() != 1 {
+        return Err(CliError::Usage(format!(
+            "Wrong number of arguments for '{}'",
+            command
+        )));
+    }
+    let dataset = read(&read_input(&args[0])?).map_err(CliError::Failed)?;
+    let summary = import::import(storage, dataset, mode, &SystemClock, dry_run)?;
+
+    for tracked_time in summary.added.iter() {
+        let project = tracked_time
+            .project_id
+            .and_then(|id| summary.projects.iter().find(|project| project.id == id))
+            .map(|project| format!(" [{}]", project.name))
+            .unwrap_or_default();
+        let tags = tracked_time
+            .tags
+            .iter()
+            .map(|tag| format!(" #{}", tag))
+            .collect::<String>();
+        println!(
+            "{}  {:>6}{}  {}{}",
+            tracked_time.date,
+            format_duration(tracked_time.duration()),
+            project,
+            tracked_time.description,
+            tags
+        );
+    }
+    let added = summary.added.len();
+    match (mode, dry_run) {
+        (ImportMode::Merge, false) => println!(
+            "Added {} entries, {} were already stored",
+            added, summary.duplicates
+        ),
+        (ImportMode::Merge, true) => println!(
+            "Would add {} entries, {} are already stored",
+            added, summary.duplicates
+        ),
+        (ImportMode::Replace, false) => println!("Replaced with {} entries", added),
+        (ImportMode::Replace, true) => println!("Would replace with {} entries", added),
+    }
+    Ok(())
+}
+
+// Reads from standard input with "-"
+fn read_input(path: &str) -> Result<String, CliError> {
+    let result = match path {
+        "-" => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).map(|_| input)
+        }
+        path => std::fs::read_to_string(path),
+    };
+    result.map_err(|error| CliError::Failed(format!("Could not read {}: {}", path, error)))
+}
+
+fn set_running(
+    storage: &SqliteStorage,
+    timer_name: Option<&str>,
+    is_running: bool,
+) -> Result<(), CliError> {
+    change_timers(storage, |tracker| {
+        let timer = select_timer(tracker, timer_name)?;
+        if tracker.timers[timer].is_running != is_running {
+            tracker.start_stop(timer);
+        }
+        Ok(())
+    })
+}
+
+fn select_timer(tracker: &Tracker, timer_name: Option<&str>) -> Result<usize, OperationError> {
+    match timer_name {
+        Some(name) => tracker.find_timer(name),
+        None => Ok(0),
+    }
+}
+
+fn change_timers(
+    storage: &SqliteStorage,
+    change: impl FnOnce(&mut Tracker) -> Result<(), OperationError>,
+) -> Result<(), CliError> {
+    let mut tracker = Tracker::load(storage, Rc::new(SystemClock))?;
+    change(&mut tracker)?;
+    storage.store_timers(&mut tracker.timers)?;
+    print_timers(&tracker);
+    Ok(())
+}
+
+// The same operations as the operations panel of the graphical interface
+fn change_entries(
+    storage: &SqliteStorage,
+    operation: impl FnOnce(&mut Tracker) -> Result<Vec<usize>, OperationError>,
+) -> Result<(), CliError> {
+    let mut tracker = Tracker::load(storage, Rc::new(SystemClock))?;
+    let changed = operation(&mut tracker)?;
+    let timers = (0..tracker.timers.len()).collect::<Vec<usize>>();
+    storage.save_changes(tracker.changes(&changed, &timers))?;
+
+    for &index in changed.iter() {
+        let tracked_time = &tracker.tracked_times[index];
+        println!(
+            "{}: {} {}",
+            index + 1,
+            format_duration(tracked_time.duration()),
+            tracked_time.description
+        );
+    }
+    print_timers(&tracker);
+    Ok(())
+}
+
+fn print_timers(tracker: &Tracker) {
+    for (i, timer) in tracker.timers.iter().enumerate() {
+        let time_box = timer
+            .remaining(tracker.clock.as_ref())
+            .map(|remaining| format!(" ({})", format_time_box(remaining)))
+            .unwrap_or_default();
+        println!(
+            "{} {}: {}{}",
+            timer.name,
+            if timer.is_running {
+                "running"
+            } else {
+                "paused"
+            },
+            format_duration(tracker.current_duration(i)),
+            time_box
+        );
+    }
+}
+
+fn format_time_box(remaining: chrono::Duration) -> String {
+    if remaining < chrono::Duration::zero() {
+        format!("{} over", format_duration(-remaining))
+    } else {
+        format!("{} left", format_duration(remaining))
+    }
+}
@@ -0,0 +1,44 @@
+use std::cell::Cell;
+use std::fmt;
+
+// Source of the current time for the timer, replaceable to drive the timer by hand
+pub trait Clock: fmt::Debug {
+    fn now(&self) -> chrono::DateTime<chrono::Utc>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+// Only moves when it is set or advanced
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Cell<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ManualClock {
+    pub fn new(now: chrono::DateTime<chrono::Utc>) -> Self {
+        ManualClock {
+            now: Cell::new(now),
+        }
+    }
+
+    pub fn set(&self, now: chrono::DateTime<chrono::Utc>) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, duration: chrono::Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.now.get()
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt;
+
+use crate::model::{self, DateRange, TrackedTime};
+use crate::tracker::Tracker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Index,
+    Id,
+    Date,
+    Description,
+    Project,
+    Client,
+    Tags,
+    // H:MM like in the list
+    Duration,
+    // Decimal hours for spreadsheets and invoices
+    Hours,
+    Seconds,
+}
+
+impl Column {
+    pub const ALL: [Column; 10] = [
+        Column::Index,
+        Column::Id,
+        Column::Date,
+        Column::Description,
+        Column::Project,
+        Column::Client,
+        Column::Tags,
+        Column::Duration,
+        Column::Hours,
+        Column::Seconds,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Index => "index",
+            Column::Id => "id",
+            Column::Date => "date",
+            Column::Description => "description",
+            Column::Project => "project",
+            Column::Client => "client",
+            Column::Tags => "tags",
+            Column::Duration => "duration",
+            Column::Hours => "hours",
+            Column::Seconds => "seconds",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Column> {
+        let name = name.trim().to_lowercase();
+        Column::ALL
+            .iter()
+            .copied()
+            .find(|column| column.name() == name)
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+pub const DEFAULT_COLUMNS: [Column; 5] = [
+    Column::Index,
+    Column::Date,
+    Column::Description,
+    Column::Duration,
+    Column::Hours,
+];
+
+// One line per entry after a header line, entries keep the number they have in the list
+pub fn export(tracker: &Tracker, columns: &[Column], range: &DateRange) -> String {
+    let mut csv = String::new();
+    write_record(&mut csv, columns.iter().map(|column| column.to_string()));
+    for (i, tracked_time) in tracker.tracked_times.iter().enumerate() {
+        if range.contains(tracked_time.date) {
+            write_record(
+                &mut csv,
+                columns
+                    .iter()
+                    .map(|column| field(tracker, i, tracked_time, *column)),
+            );
+        }
+    }
+    csv
+}
+
+fn field(tracker: &Tracker, i: usize, tracked_time: &TrackedTime, column: Column) -> String {
+    let project = tracked_time.project_id.and_then(|id| tracker.project(id));
+    let duration = tracked_time.duration();
+    match column {
+        Column::Index => (i + 1).to_string(),
+        Column::Id => tracked_time.id.map(|id| id.to_string()).unwrap_or_default(),
+        Column::Date => tracked_time.date.format("%Y-%m-%d").to_string(),
+        Column::Description => tracked_time.description.clone(),
+        Column::Project => project
+            .map(|project| project.name.clone())
+            .unwrap_or_default(),
+        Column::Client => project
+            .and_then(|project| project.client_id)
+            .and_then(|id| tracker.client(id))
+            .map(|client| client.name.clone())
+            .unwrap_or_default(),
+        Column::Tags => tracked_time.tags.join(" "),
+        Column::Duration => model::format_duration(duration),
+        Column::Hours => format!("{:.2}", duration.num_seconds() as f64 / 3600.0),
+        Column::Seconds => duration.num_seconds().to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    // Line of the opening quote, starting at 1
+    pub line: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The quote opened on line {} is never closed", self.line)
+    }
+}
+
+// Reads records as written by spreadsheets, quoted fields may contain separators and line breaks.
+// Empty lines are skipped.
+pub fn parse(input: &str) -> Result<Vec<Vec<String>>, ParseError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut quote_line = None;
+    let mut chars = input.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        match (quote_line, c) {
+            (Some(_), '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (Some(_), '"') => quote_line = None,
+            (Some(_), c) => field.push(c),
+            (None, '"') if field.is_empty() => quote_line = Some(line),
+            (None, ',') => record.push(std::mem::take(&mut field)),
+            (None, '\r') if chars.peek() == Some(&'\n') => {}
+            (None, '\n') | (None, '\r') => {
+                record.push(std::mem::take(&mut field));
+                let record = std::mem::take(&mut record);
+                if !is_empty_line(&record) {
+                    records.push(record);
+                }
+            }
+            (None, c) => field.push(c),
+        }
+    }
+    if let Some(line) = quote_line {
+        return Err(ParseError { line });
+    }
+    record.push(field);
+    if !is_empty_line(&record) {
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn is_empty_line(record: &[String]) -> bool {
+    record.len() == 1 && record[0].is_empty()
+}
+
+// Lines end with CRLF as in RFC 4180
+fn write_record(csv: &mut String, fields: impl Iterator<Item = String>) {
+    let fields = fields.map(|field| quote(&field)).collect::<Vec<String>>();
+    csv.push_str(&fields.join(","));
+    csv.push_str("\r\n");
+}
+
+// Fields containing separators, quotes or line breaks are quoted with inner quotes doubled
+fn quote(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::model::{Client, Interval, IntervalSource, Project};
+    use chrono::TimeZone;
+    use std::rc::Rc;
+
+    fn time(day: u32, hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.ymd(2021, 6, day).and_hms(hour, minute, 0)
+    }
+
+    // An entry in the project "Web" of "Acme" on June 1st and one with quotes on June 2nd
+    fn tracker() -> Tracker {
+        let mut tracker = Tracker::empty(Rc::new(ManualClock::new(time(2, 18, 0))));
+        tracker.clients.push(Client {
+            id: 2,
+            name: String::from("Acme"),
+        });
+        tracker.projects.push(Project {
+            id: 1,
+            name: String::from("Web"),
+            client_id: Some(2),
+            color: 0x3a86ff,
+        });
+        let mut tracked_time = TrackedTime::new(
+            String::from("Fix login, again"),
+            vec![Interval::new(
+                time(1, 9, 0),
+                time(1, 10, 30),
+                IntervalSource::Timer,
+            )],
+            time(1, 12, 0),
+        );
+        tracked_time.id = Some(4);
+        tracked_time.tags = vec![String::from("web"), String::from("bug")];
+        tracked_time.project_id = Some(1);
+        tracker.tracked_times.push(tracked_time);
+        tracker.tracked_times.push(TrackedTime::new(
+            String::from("Say \"hi\"\nto the team"),
+            vec![Interval::new(
+                time(2, 9, 0),
+                time(2, 9, 20),
+                IntervalSource::Manual,
+            )],
+            time(2, 12, 0),
+        ));
+        tracker
+    }
+
+    #[test]
+    fn writes_the_default_columns() {
+        assert_eq!(
+            export(&tracker(), &DEFAULT_COLUMNS, &DateRange::default()),
+            "index,date,description,duration,hours\r\n\
+            1,2021-06-01,\"Fix login, again\",1:30,1.50\r\n\
+            2,2021-06-02,\"Say \"\"hi\"\"\nto the team\",0:20,0.33\r\n"
+        );
+    }
+
+    #[test]
+    fn writes_chosen_columns_of_entries_in_the_range() {
+        let range = DateRange {
+            from: model::parse_date("2021-06-01"),
+            to: model::parse_date("2021-06-01"),
+        };
+        let columns = [
+            Column::Id,
+            Column::Project,
+            Column::Client,
+            Column::Tags,
+            Column::Seconds,
+        ];
+        assert_eq!(
+            export(&tracker(), &columns, &range),
+            "id,project,client,tags,seconds\r\n4,Web,Acme,web bug,5400\r\n"
+        );
+    }
+
+    #[test]
+    fn reads_its_own_export() {
+        let records = parse(&export(&tracker(), &DEFAULT_COLUMNS, &DateRange::default())).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1][2], "Fix login, again");
+        assert_eq!(records[2][2], "Say \"hi\"\nto the team");
+        assert_eq!(records[2][4], "0.33");
+    }
+
+    #[test]
+    fn reads_records_as_written_by_spreadsheets() {
+        let input = "\u{feff}a,b\r\n\r\n\"1\r\n2\",\"\"\r\nx,y\"z\"\n,";
+        assert_eq!(
+            parse(input),
+            Ok(vec![
+                vec![String::from("a"), String::from("b")],
+                vec![String::from("1\r\n2"), String::new()],
+                vec![String::from("x"), String::from("y\"z\"")],
+                vec![String::new(), String::new()],
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_an_unclosed_quote() {
+        assert_eq!(parse("a,b\n\"c\nd,e\n"), Err(ParseError { line: 2 }));
+    }
+
+    #[test]
+    fn finds_columns_by_name() {
+        assert_eq!(Column::parse(" Hours "), Some(Column::Hours));
+        assert_eq!(Column::parse("minutes"), None);
+        assert!(Column::ALL
+            .iter()
+            .all(|column| Column::parse(column.name()) == Some(*column)));
+    }
+}
@@ -1,98 +1,553 @@
-use rusqlite::{params, Connection};
-use std::collections::HashMap;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::{SimpleTimeTracker, TrackedTime};
+use crate::clock::Clock;
+use crate::migrations;
+use crate::model::{Client, Interval, IntervalSource, Project, TrackedTime};
+use crate::storage::{Changes, Dataset, Storage};
+use crate::timer::Timer;
 
-pub const TIME_KEY: &str = "time";
-pub const PAUSED_KEY: &str = "paused";
-pub const DARKMODE_KEY: &str = "darkmode";
+pub const DATABASE_ENV_VAR: &str = "SIMPLE_TIME_TRACKER_DB";
+const DATABASE_FILE: &str = "simple_time_tracker.sqlite";
 
-pub fn load_states() -> HashMap<String, i32> {
-    let db = Connection::open("simple_time_tracker.sqlite").unwrap();
+const DATE_FORMAT: &str = "%Y-%m-%d";
 
-    db.execute(
-        "CREATE TABLE IF NOT EXISTS States (
-                Key TEXT PRIMARY KEY,
-                Value INTEGER NOT NULL
-        )",
-        [],
-    )
-    .unwrap();
+#[derive(Debug)]
+pub enum Error {
+    Locked,
+    Corrupt,
+    InvalidData(String),
+    UnsupportedVersion(i64),
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
 
-    let mut stmt = db.prepare("SELECT Key, Value FROM States").unwrap();
-    let mut rows = stmt.query([]).unwrap();
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
 
-    let mut states: HashMap<String, i32> = HashMap::new();
-    while let Some(row) = rows.next().unwrap() {
-        states.insert(row.get(0).unwrap(), row.get(1).unwrap());
+        match &error {
+            rusqlite::Error::SqliteFailure(failure, _) => match failure.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Error::Locked,
+                ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => Error::Corrupt,
+                _ => Error::Sqlite(error),
+            },
+            _ => Error::Sqlite(error),
+        }
     }
-    return states;
 }
 
-pub fn load_tracked_times() -> Vec<TrackedTime> {
-    let db = Connection::open("simple_time_tracker.sqlite").unwrap();
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
 
-    db.execute(
-        "CREATE TABLE IF NOT EXISTS TrackedTimes (
-                ID INTEGER PRIMARY KEY,
-                Seconds INTEGER NOT NULL,
-                Description TEXT NOT NULL
-        )",
-        [],
-    )
-    .unwrap();
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Locked => write!(f, "The database is locked by another process"),
+            Error::Corrupt => write!(f, "The database file is corrupt or not a database"),
+            Error::InvalidData(message) => write!(f, "Invalid data in database: {}", message),
+            Error::UnsupportedVersion(version) => write!(
+                f,
+                "The database has schema version {} and was created by a newer version",
+                version
+            ),
+            Error::Io(error) => write!(f, "Could not access the database file: {}", error),
+            Error::Sqlite(error) => write!(f, "Database error: {}", error),
+        }
+    }
+}
+
+pub fn default_path() -> PathBuf {
+    match dirs::data_dir() {
+        Some(data_dir) => data_dir.join("simple_time_tracker").join(DATABASE_FILE),
+        None => PathBuf::from(DATABASE_FILE),
+    }
+}
 
-    let mut stmt = db
-        .prepare("SELECT Seconds, Description FROM TrackedTimes")
-        .unwrap();
-    let mut rows = stmt.query([]).unwrap();
+// Older versions stored the database in the working directory, move it to the given path once
+pub fn adopt_legacy_database(path: &Path) -> Result<()> {
+    let legacy_path = Path::new(DATABASE_FILE);
+    if path == legacy_path || path.exists() || !legacy_path.is_file() {
+        return Ok(());
+    }
 
-    let mut tracked_times = Vec::new();
-    while let Some(row) = rows.next().unwrap() {
-        tracked_times.push(TrackedTime::new(
-            chrono::Duration::seconds(row.get(0).unwrap()),
-            row.get(1).unwrap(),
-        ));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(legacy_path, path)?;
+    fs::rename(legacy_path, format!("{}.migrated", DATABASE_FILE))?;
+    Ok(())
+}
+
+pub struct SqliteStorage {
+    path: PathBuf,
+}
+
+impl SqliteStorage {
+    pub fn new(path: PathBuf) -> Self {
+        SqliteStorage { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut db = Connection::open(&self.path)?;
+        migrations::run(&mut db, &self.path)?;
+        Ok(db)
     }
-    return tracked_times;
 }
 
-impl SimpleTimeTracker {
-    pub fn store_state(&self) {
-        let db = Connection::open("simple_time_tracker.sqlite").unwrap();
+impl Storage for SqliteStorage {
+    fn load_timers(&self, clock: &dyn Clock) -> Result<Vec<Timer>> {
+        let db = self.open()?;
+
+        let mut stmt = db.prepare(
+            "SELECT ID, Name, RunningSince, PausedSeconds, TimeBoxSeconds FROM Timers ORDER BY ID",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut timers = Vec::new();
+        while let Some(row) = rows.next()? {
+            let running_since: Option<i64> = row.get(2)?;
+            let mut timer = match running_since {
+                Some(running_since) => Timer::running_since(from_timestamp(running_since), clock),
+                None => Timer::paused(chrono::Duration::seconds(row.get(3)?), clock),
+            };
+            timer.id = Some(row.get(0)?);
+            timer.name = row.get(1)?;
+            timer.time_box = row.get::<_, Option<i64>>(4)?.map(chrono::Duration::seconds);
+            timers.push(timer);
+        }
+        Ok(timers)
+    }
+
+    fn store_timers(&self, timers: &mut [Timer]) -> Result<()> {
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
+
+        let stored_ids = {
+            let mut stmt = transaction.prepare("SELECT ID FROM Timers")?;
+            let ids = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<i64>>>()?;
+            ids
+        };
+        for id in stored_ids {
+            if timers.iter().all(|timer| timer.id != Some(id)) {
+                transaction.execute("DELETE FROM Timers WHERE ID = ?1", params![id])?;
+            }
+        }
+
+        for timer in timers.iter_mut() {
+            write_timer(&transaction, timer)?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn load_setting(&self, key: &str) -> Result<Option<i64>> {
+        let db = self.open()?;
+        load_state(&db, key)
+    }
+
+    fn store_setting(&self, key: &str, value: i64) -> Result<()> {
+        let db = self.open()?;
+        db.execute(
+            "INSERT OR REPLACE INTO States (Key, Value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    fn load_settings(&self) -> Result<Vec<(String, i64)>> {
+        let db = self.open()?;
+
+        let mut stmt = db.prepare("SELECT Key, Value FROM States ORDER BY Key")?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, i64)>>>()?;
+        Ok(settings)
+    }
+
+    fn load_tracked_times(&self) -> Result<Vec<TrackedTime>> {
+        let db = self.open()?;
+
+        let mut stmt = db.prepare(
+            "SELECT ID, Description, Date, Created, Modified, ProjectID, PlannedSeconds
+                FROM TrackedTimes ORDER BY ID",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut intervals_stmt = db.prepare(
+            "SELECT ID, Start, End, Source FROM Intervals WHERE TrackedTimeID = ?1 ORDER BY Start",
+        )?;
+
+        let mut tags_stmt = db.prepare(
+            "SELECT Tags.Name FROM TrackedTimeTags
+                JOIN Tags ON Tags.ID = TrackedTimeTags.TagID
+                WHERE TrackedTimeTags.TrackedTimeID = ?1
+                ORDER BY Tags.Name",
+        )?;
+
+        let mut tracked_times = Vec::new();
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let mut interval_rows = intervals_stmt.query(params![id])?;
+            let mut intervals = Vec::new();
+            while let Some(interval_row) = interval_rows.next()? {
+                let source: String = interval_row.get(3)?;
+                let mut interval = Interval::new(
+                    from_timestamp(interval_row.get(1)?),
+                    from_timestamp(interval_row.get(2)?),
+                    IntervalSource::parse(&source),
+                );
+                interval.id = Some(interval_row.get(0)?);
+                intervals.push(interval);
+            }
+
+            let date: String = row.get(2)?;
+            let mut tracked_time = TrackedTime::with_timestamps(
+                row.get(1)?,
+                intervals,
+                chrono::NaiveDate::parse_from_str(&date, DATE_FORMAT)
+                    .map_err(|_| Error::InvalidData(format!("invalid date '{}'", date)))?,
+                from_timestamp(row.get(3)?),
+                from_timestamp(row.get(4)?),
+            );
+            tracked_time.id = Some(id);
+            tracked_time.project_id = row.get(5)?;
+            tracked_time.planned = row.get::<_, Option<i64>>(6)?.map(chrono::Duration::seconds);
+            tracked_time.tags = tags_stmt
+                .query_map(params![id], |tag_row| tag_row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            tracked_times.push(tracked_time);
+        }
+        Ok(tracked_times)
+    }
+
+    // Inserts the entry if it has not been stored yet, otherwise updates its row and sessions
+    fn save_tracked_time(&self, tracked_time: &mut TrackedTime) -> Result<()> {
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
+        write_tracked_time(&transaction, tracked_time)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn delete_tracked_time(&self, tracked_time: &TrackedTime) -> Result<()> {
+        let id = match tracked_time.id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
+        delete_tracked_time_rows(&transaction, id)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn save_changes(&self, changes: Changes) -> Result<()> {
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
+        for &id in changes.deleted_tracked_times.iter() {
+            delete_tracked_time_rows(&transaction, id)?;
+        }
+        for &id in changes.deleted_timers.iter() {
+            transaction.execute("DELETE FROM Timers WHERE ID = ?1", params![id])?;
+        }
+        for tracked_time in changes.tracked_times {
+            write_tracked_time(&transaction, tracked_time)?;
+        }
+        for timer in changes.timers {
+            write_timer(&transaction, timer)?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn load_clients(&self) -> Result<Vec<Client>> {
+        let db = self.open()?;
+
+        let mut stmt = db.prepare("SELECT ID, Name FROM Clients ORDER BY Name")?;
+        let clients = stmt
+            .query_map([], |row| {
+                Ok(Client {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<Client>>>()?;
+        Ok(clients)
+    }
+
+    fn load_projects(&self) -> Result<Vec<Project>> {
+        let db = self.open()?;
+
+        let mut stmt =
+            db.prepare("SELECT ID, Name, ClientID, Color FROM Projects ORDER BY Name")?;
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    client_id: row.get(2)?,
+                    color: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<Project>>>()?;
+        Ok(projects)
+    }
 
-        db.execute("DELETE FROM States", []).unwrap();
-        let mut stmt = db
-            .prepare("INSERT INTO States (Key, Value) VALUES (?1, ?2)")
-            .unwrap();
+    fn insert_project(&self, name: &str, client_name: &str, color: u32) -> Result<i64> {
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
 
-        let time = if self.is_running {
-            self.start_time.timestamp()
+        let client_id = if client_name.is_empty() {
+            None
         } else {
-            self.get_current_duration().num_seconds()
+            transaction.execute(
+                "INSERT OR IGNORE INTO Clients (Name) VALUES (?1)",
+                params![client_name],
+            )?;
+            Some(transaction.query_row(
+                "SELECT ID FROM Clients WHERE Name = ?1",
+                params![client_name],
+                |row| row.get::<_, i64>(0),
+            )?)
         };
+        transaction.execute(
+            "INSERT INTO Projects (Name, ClientID, Color) VALUES (?1, ?2, ?3)",
+            params![name, client_id, color],
+        )?;
+        let id = transaction.last_insert_rowid();
+
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    fn delete_project(&self, id: i64) -> Result<()> {
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
+        transaction.execute(
+            "UPDATE TrackedTimes SET ProjectID = NULL WHERE ProjectID = ?1",
+            params![id],
+        )?;
+        transaction.execute("DELETE FROM Projects WHERE ID = ?1", params![id])?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn replace_all(&self, dataset: &mut Dataset) -> Result<()> {
+        let mut db = self.open()?;
+        migrations::backup(&db, &self.path, "import")?;
+        let transaction = db.transaction()?;
+
+        for table in [
+            "TrackedTimeTags",
+            "Tags",
+            "Intervals",
+            "TrackedTimes",
+            "Projects",
+            "Clients",
+            "Timers",
+            "States",
+        ]
+        .iter()
+        {
+            transaction.execute(&format!("DELETE FROM {}", table), [])?;
+        }
+
+        insert_dataset(&transaction, dataset)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn insert_all(&self, dataset: &mut Dataset) -> Result<()> {
+        let mut db = self.open()?;
+        let transaction = db.transaction()?;
+        insert_dataset(&transaction, dataset)?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+fn insert_dataset(db: &Connection, dataset: &mut Dataset) -> Result<()> {
+    for (key, value) in dataset.settings.iter() {
+        db.execute(
+            "INSERT OR IGNORE INTO States (Key, Value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+    }
+    for client in dataset.clients.iter() {
+        db.execute(
+            "INSERT INTO Clients (ID, Name) VALUES (?1, ?2)",
+            params![client.id, client.name],
+        )?;
+    }
+    for project in dataset.projects.iter() {
+        db.execute(
+            "INSERT INTO Projects (ID, Name, ClientID, Color) VALUES (?1, ?2, ?3, ?4)",
+            params![project.id, project.name, project.client_id, project.color],
+        )?;
+    }
+    for timer in dataset.timers.iter_mut() {
+        write_timer(db, timer)?;
+    }
+    for tracked_time in dataset.tracked_times.iter_mut() {
+        write_tracked_time(db, tracked_time)?;
+    }
+    Ok(())
+}
+
+fn load_state(db: &Connection, key: &str) -> Result<Option<i64>> {
+    let value = db
+        .query_row(
+            "SELECT Value FROM States WHERE Key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value)
+}
+
+pub fn from_timestamp(timestamp: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_utc(
+        chrono::NaiveDateTime::from_timestamp(timestamp, 0),
+        chrono::Utc,
+    )
+}
 
-        stmt.execute(params![TIME_KEY, time]).unwrap();
+fn delete_tracked_time_rows(db: &Connection, id: i64) -> Result<()> {
+    db.execute(
+        "DELETE FROM TrackedTimeTags WHERE TrackedTimeID = ?1",
+        params![id],
+    )?;
+    db.execute(
+        "DELETE FROM Intervals WHERE TrackedTimeID = ?1",
+        params![id],
+    )?;
+    db.execute("DELETE FROM TrackedTimes WHERE ID = ?1", params![id])?;
+    Ok(())
+}
 
-        stmt.execute(params![PAUSED_KEY, !self.is_running]).unwrap();
+fn write_tracked_time(db: &Connection, tracked_time: &mut TrackedTime) -> Result<()> {
+    // An ID of NULL lets SQLite assign a new one, a known ID is kept even if its row was deleted
+    db.execute(
+        "INSERT INTO TrackedTimes
+                (ID, Seconds, Description, Date, Created, Modified, ProjectID, PlannedSeconds)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(ID) DO UPDATE SET Seconds = excluded.Seconds,
+                Description = excluded.Description, Date = excluded.Date,
+                Created = excluded.Created, Modified = excluded.Modified,
+                ProjectID = excluded.ProjectID, PlannedSeconds = excluded.PlannedSeconds",
+        params![
+            tracked_time.id,
+            tracked_time.duration().num_seconds(),
+            tracked_time.description,
+            tracked_time.date.format(DATE_FORMAT).to_string(),
+            tracked_time.created_at.timestamp(),
+            tracked_time.modified_at.timestamp(),
+            tracked_time.project_id,
+            tracked_time.planned.map(|planned| planned.num_seconds())
+        ],
+    )?;
+    let id = match tracked_time.id {
+        Some(id) => id,
+        None => db.last_insert_rowid(),
+    };
 
-        stmt.execute(params![DARKMODE_KEY, self.is_dark_mode as i32])
-            .unwrap();
+    db.execute(
+        "DELETE FROM TrackedTimeTags WHERE TrackedTimeID = ?1",
+        params![id],
+    )?;
+    for tag in tracked_time.tags.iter() {
+        db.execute(
+            "INSERT OR IGNORE INTO Tags (Name) VALUES (?1)",
+            params![tag],
+        )?;
+        // A tag given twice is stored once
+        db.execute(
+            "INSERT OR IGNORE INTO TrackedTimeTags (TrackedTimeID, TagID)
+                SELECT ?1, ID FROM Tags WHERE Name = ?2",
+            params![id, tag],
+        )?;
     }
 
-    pub fn store_tracked_times(&self) {
-        let db = Connection::open("simple_time_tracker.sqlite").unwrap();
+    // Remove sessions that were deleted from the entry
+    let stored_interval_ids = {
+        let mut stmt = db.prepare("SELECT ID FROM Intervals WHERE TrackedTimeID = ?1")?;
+        let ids = stmt
+            .query_map(params![id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        ids
+    };
+    for interval_id in stored_interval_ids {
+        if tracked_time
+            .intervals
+            .iter()
+            .all(|interval| interval.id != Some(interval_id))
+        {
+            db.execute("DELETE FROM Intervals WHERE ID = ?1", params![interval_id])?;
+        }
+    }
 
-        db.execute("DELETE FROM TrackedTimes", []).unwrap();
-        let mut stmt = db
-            .prepare("INSERT INTO TrackedTimes (Seconds, Description) VALUES (?1, ?2)")
-            .unwrap();
-        for tracked_time in self.tracked_times.iter() {
-            stmt.execute(params![
-                tracked_time.duration.num_seconds(),
-                tracked_time.description
-            ])
-            .unwrap();
+    for interval in tracked_time.intervals.iter_mut() {
+        db.execute(
+            "INSERT INTO Intervals (ID, TrackedTimeID, Start, End, Source)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(ID) DO UPDATE SET TrackedTimeID = excluded.TrackedTimeID,
+                    Start = excluded.Start, End = excluded.End, Source = excluded.Source",
+            params![
+                interval.id,
+                id,
+                interval.start.timestamp(),
+                interval.end.timestamp(),
+                interval.source.as_str()
+            ],
+        )?;
+        if interval.id.is_none() {
+            interval.id = Some(db.last_insert_rowid());
         }
     }
+
+    tracked_time.id = Some(id);
+    Ok(())
+}
+
+// A running timer stores when it started, a paused one the time it shows
+fn write_timer(db: &Connection, timer: &mut Timer) -> Result<()> {
+    let (running_since, paused_seconds) = if timer.is_running {
+        (Some(timer.start_time.timestamp()), 0)
+    } else {
+        (None, (timer.pause_time - timer.start_time).num_seconds())
+    };
+    db.execute(
+        "INSERT INTO Timers (ID, Name, RunningSince, PausedSeconds, TimeBoxSeconds)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(ID) DO UPDATE SET Name = excluded.Name,
+                RunningSince = excluded.RunningSince, PausedSeconds = excluded.PausedSeconds,
+                TimeBoxSeconds = excluded.TimeBoxSeconds",
+        params![
+            timer.id,
+            timer.name,
+            running_since,
+            paused_seconds,
+            timer.time_box.map(|time_box| time_box.num_seconds())
+        ],
+    )?;
+    if timer.id.is_none() {
+        timer.id = Some(db.last_insert_rowid());
+    }
+    Ok(())
 }
@@ -0,0 +1,77 @@
+use crate::model::TrackedTime;
+use crate::timer::Timer;
+use crate::tracker::Tracker;
+
+// Older states are dropped once this many changes can be undone
+const MAX_SNAPSHOTS: usize = 100;
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    timers: Vec<Timer>,
+    tracked_times: Vec<TrackedTime>,
+}
+
+impl Snapshot {
+    fn of(tracker: &Tracker) -> Self {
+        Snapshot {
+            timers: tracker.timers.clone(),
+            tracked_times: tracker.tracked_times.clone(),
+        }
+    }
+
+    fn restore(self, tracker: &mut Tracker) {
+        tracker.timers = self.timers;
+        tracker.tracked_times = self.tracked_times;
+    }
+}
+
+// Undo and redo of changes to the timer and the entries, projects are not covered
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    // Call before changing the tracker
+    pub fn record(&mut self, tracker: &Tracker) {
+        if self.undo.len() == MAX_SNAPSHOTS {
+            self.undo.remove(0);
+        }
+        self.undo.push(Snapshot::of(tracker));
+        self.redo.clear();
+    }
+
+    // Drops the last recorded state if the change did not happen after all
+    pub fn discard(&mut self) {
+        self.undo.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // Returns false if there is nothing to undo
+    pub fn undo(&mut self, tracker: &mut Tracker) -> bool {
+        match self.undo.pop() {
+            Some(snapshot) => {
+                self.redo.push(Snapshot::of(tracker));
+                snapshot.restore(tracker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, tracker: &mut Tracker) -> bool {
+        match self.redo.pop() {
+            Some(snapshot) => {
+                self.undo.push(Snapshot::of(tracker));
+                snapshot.restore(tracker);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::clock::Clock;
+use crate::model::DateRange;
+use crate::tracker::Tracker;
+
+// Times are written in UTC so calendars show them in their own time zone
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// An iCalendar file as in RFC 5545 with one event per session. The summary is the description,
+// the categories are the tags and the project.
+pub fn export(tracker: &Tracker, range: &DateRange, clock: &dyn Clock) -> String {
+    let stamp = clock.now().format(TIME_FORMAT).to_string();
+    let mut ics = String::new();
+    write_line(&mut ics, "BEGIN:VCALENDAR");
+    write_line(&mut ics, "VERSION:2.0");
+    write_line(&mut ics, "PRODID:-//simple_time_tracker//EN");
+    write_line(&mut ics, "CALSCALE:GREGORIAN");
+    for tracked_time in tracker.tracked_times.iter() {
+        if !range.contains(tracked_time.date) {
+            continue;
+        }
+        let project = tracked_time.project_id.and_then(|id| tracker.project(id));
+        let mut categories = tracked_time
+            .tags
+            .iter()
+            .map(|tag| escape(tag))
+            .collect::<Vec<String>>();
+        if let Some(project) = project {
+            categories.push(escape(&project.name));
+        }
+        for interval in tracked_time.intervals.iter() {
+            // Stable so importing the file again updates the events instead of adding them twice.
+            // Row IDs of sessions change when time is cut or moved and on replacing imports.
+            let start = interval.start.format(TIME_FORMAT);
+            let uid = match tracked_time.id {
+                Some(id) => format!("entry-{}-{}", id, start),
+                None => format!("session-{}", start),
+            };
+            write_line(&mut ics, "BEGIN:VEVENT");
+            write_line(&mut ics, &format!("UID:{}@simple_time_tracker", uid));
+            write_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+            write_line(
+                &mut ics,
+                &format!("DTSTART:{}", interval.start.format(TIME_FORMAT)),
+            );
+            write_line(
+                &mut ics,
+                &format!("DTEND:{}", interval.end.format(TIME_FORMAT)),
+            );
+            write_line(
+                &mut ics,
+                &format!("SUMMARY:{}", escape(&tracked_time.description)),
+            );
+            if !categories.is_empty() {
+                write_line(&mut ics, &format!("CATEGORIES:{}", categories.join(",")));
+            }
+            if let Some(project) = project {
+                let client = project.client_id.and_then(|id| tracker.client(id));
+                let description = match client {
+                    Some(client) => format!("Project: {} ({})", project.name, client.name),
+                    None => format!("Project: {}", project.name),
+                };
+                write_line(&mut ics, &format!("DESCRIPTION:{}", escape(&description)));
+            }
+            write_line(
+                &mut ics,
+                &format!(
+                    "LAST-MODIFIED:{}",
+                    tracked_time.modified_at.format(TIME_FORMAT)
+                ),
+            );
+            // Tracked time does not block the calendar
+            write_line(&mut ics, "TRANSP:TRANSPARENT");
+            write_line(&mut ics, "END:VEVENT");
+        }
+    }
+    write_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+// Line breaks of any kind become an escaped newline, a bare CR would end the line early
+fn escape(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Lines end with CRLF and are folded after 75 bytes without splitting characters
+fn write_line(ics: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            // The space starting the continuation counts towards its length
+            length = 1;
+        }
+        ics.push(c);
+        length += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::model::{Client, Interval, IntervalSource, Project, TrackedTime};
+    use chrono::TimeZone;
+    use std::rc::Rc;
+
+    fn time(hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.ymd(2021, 6, 1).and_hms(hour, minute, 0)
+    }
+
+    // An entry with ID 4 tagged "web" in the project "Web" of the client "Acme"
+    fn tracker() -> Tracker {
+        let mut tracker = Tracker::empty(Rc::new(ManualClock::new(time(18, 0))));
+        tracker.clients.push(Client {
+            id: 2,
+            name: String::from("Acme"),
+        });
+        tracker.projects.push(Project {
+            id: 1,
+            name: String::from("Web"),
+            client_id: Some(2),
+            color: 0x3a86ff,
+        });
+        let mut tracked_time = TrackedTime::new(
+            String::from("Fix login, again"),
+            vec![Interval::new(
+                time(9, 0),
+                time(10, 0),
+                IntervalSource::Timer,
+            )],
+            time(12, 0),
+        );
+        tracked_time.id = Some(4);
+        tracked_time.tags = vec![String::from("web")];
+        tracked_time.project_id = Some(1);
+        tracker.tracked_times.push(tracked_time);
+        tracker
+    }
+
+    fn export_tracker(tracker: &Tracker) -> String {
+        export(tracker, &DateRange::default(), tracker.clock.as_ref())
+    }
+
+    #[test]
+    fn writes_one_event_per_session() {
+        assert_eq!(
+            export_tracker(&tracker()),
+            "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            PRODID:-//simple_time_tracker//EN\r\n\
+            CALSCALE:GREGORIAN\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:entry-4-20210601T090000Z@simple_time_tracker\r\n\
+            DTSTAMP:20210601T180000Z\r\n\
+            DTSTART:20210601T090000Z\r\n\
+            DTEND:20210601T100000Z\r\n\
+            SUMMARY:Fix login\\, again\r\n\
+            CATEGORIES:web,Web\r\n\
+            DESCRIPTION:Project: Web (Acme)\r\n\
+            LAST-MODIFIED:20210601T120000Z\r\n\
+            TRANSP:TRANSPARENT\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n"
+        );
+    }
+
+    #[test]
+    fn keeps_uids_when_sessions_are_stored_again() {
+        let mut tracker = tracker();
+        let before = export_tracker(&tracker);
+        // Cutting and replacing imports give the sessions new rows
+        tracker.tracked_times[0].intervals[0].id = Some(31);
+        tracker.tracked_times[0].intervals.push(Interval::new(
+            time(11, 0),
+            time(11, 30),
+            IntervalSource::Manual,
+        ));
+        let after = export_tracker(&tracker);
+
+        let uids = |ics: &str| {
+            ics.lines()
+                .filter(|line| line.starts_with("UID:"))
+                .map(String::from)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            uids(&before),
+            vec!["UID:entry-4-20210601T090000Z@simple_time_tracker"]
+        );
+        assert_eq!(uids(&after)[0], uids(&before)[0]);
+        assert_eq!(
+            uids(&after)[1],
+            "UID:entry-4-20210601T110000Z@simple_time_tracker"
+        );
+    }
+
+    #[test]
+    fn escapes_line_breaks_of_any_kind() {
+        assert_eq!(escape("a\r\nb\rc\nd"), "a\\nb\\nc\\nd");
+        assert_eq!(escape("a;b,c\\d"), "a\\;b\\,c\\\\d");
+    }
+
+    #[test]
+    fn folds_long_lines_between_characters() {
+        let mut ics = String::new();
+        let summary = format!("SUMMARY:{}", "ä".repeat(60));
+        write_line(&mut ics, &summary);
+
+        let lines = ics.split("\r\n").collect::<Vec<&str>>();
+        assert!(lines.len() > 2);
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(lines[1..lines.len() - 1]
+            .iter()
+            .all(|line| line.starts_with(' ')));
+        assert_eq!(lines.last(), Some(&""));
+        assert_eq!(ics.replace("\r\n ", ""), format!("{}\r\n", summary));
+    }
+}
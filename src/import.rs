@@ -0,0 +1,286 @@
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::database::Result;
+use crate::model::{self, Client, Interval, IntervalSource, Project, TrackedTime, PROJECT_COLORS};
+use crate::storage::{Dataset, Storage};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    // Adds what is not stored yet and keeps everything else
+    Merge,
+    // Drops everything stored before
+    Replace,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub added: Vec<TrackedTime>,
+    // Entries that were already stored and left out
+    pub duplicates: usize,
+    // The projects the added entries refer to, including new ones
+    pub projects: Vec<Project>,
+}
+
+// Stores imported data, IDs of imported entries are only kept when replacing.
+// A dry run only reports what would be added.
+pub fn import(
+    storage: &impl Storage,
+    mut imported: Dataset,
+    mode: ImportMode,
+    clock: &dyn Clock,
+    dry_run: bool,
+) -> Result<ImportSummary> {
+    match mode {
+        ImportMode::Merge => {
+            let stored = Dataset::load(storage, clock)?;
+            let mut dataset = stored.clone();
+            let mut summary = merge(&mut dataset, imported);
+            summary.projects = dataset.projects.clone();
+            if !dry_run {
+                // Merging only appends, so only the rows after the stored ones are inserted
+                storage.insert_all(&mut Dataset {
+                    settings: dataset.settings.split_off(stored.settings.len()),
+                    clients: dataset.clients.split_off(stored.clients.len()),
+                    projects: dataset.projects.split_off(stored.projects.len()),
+                    timers: dataset.timers.split_off(stored.timers.len()),
+                    tracked_times: dataset.tracked_times.split_off(stored.tracked_times.len()),
+                })?;
+            }
+            Ok(summary)
+        }
+        ImportMode::Replace => {
+            let summary = ImportSummary {
+                added: imported.tracked_times.clone(),
+                projects: imported.projects.clone(),
+                ..ImportSummary::default()
+            };
+            if !dry_run {
+                storage.replace_all(&mut imported)?;
+            }
+            Ok(summary)
+        }
+    }
+}
+
+// Clients, projects and timers are matched by name, settings that are already set are kept
+pub fn merge(dataset: &mut Dataset, imported: Dataset) -> ImportSummary {
+    for (key, value) in imported.settings {
+        if dataset.settings.iter().all(|(k, _)| *k != key) {
+            dataset.settings.push((key, value));
+        }
+    }
+
+    let mut client_ids = Vec::new();
+    for mut client in imported.clients {
+        let imported_id = client.id;
+        let id = match dataset.clients.iter().find(|c| c.name == client.name) {
+            Some(existing) => existing.id,
+            None => {
+                client.id = next_id(dataset.clients.iter().map(|c| c.id));
+                let id = client.id;
+                dataset.clients.push(client);
+                id
+            }
+        };
+        client_ids.push((imported_id, id));
+    }
+
+    let mut project_ids = Vec::new();
+    for mut project in imported.projects {
+        let imported_id = project.id;
+        project.client_id = project.client_id.and_then(|id| map_id(&client_ids, id));
+        let existing = dataset
+            .projects
+            .iter()
+            .find(|p| p.name == project.name && p.client_id == project.client_id);
+        let id = match existing {
+            Some(existing) => existing.id,
+            None => {
+                project.id = next_id(dataset.projects.iter().map(|p| p.id));
+                let id = project.id;
+                dataset.projects.push(project);
+                id
+            }
+        };
+        project_ids.push((imported_id, id));
+    }
+
+    for mut timer in imported.timers {
+        if dataset
+            .timers
+            .iter()
+            .all(|t| t.name.to_lowercase() != timer.name.to_lowercase())
+        {
+            timer.id = None;
+            dataset.timers.push(timer);
+        }
+    }
+
+    let mut summary = ImportSummary::default();
+    for mut tracked_time in imported.tracked_times {
+        if dataset
+            .tracked_times
+            .iter()
+            .any(|t| is_duplicate(t, &tracked_time))
+        {
+            summary.duplicates += 1;
+            continue;
+        }
+        tracked_time.id = None;
+        tracked_time.project_id = tracked_time
+            .project_id
+            .and_then(|id| map_id(&project_ids, id));
+        for interval in tracked_time.intervals.iter_mut() {
+            interval.id = None;
+        }
+        summary.added.push(tracked_time.clone());
+        dataset.tracked_times.push(tracked_time);
+    }
+    summary
+}
+
+// Entries with the same description and sessions are the same work whatever their IDs are
+pub fn is_duplicate(a: &TrackedTime, b: &TrackedTime) -> bool {
+    a.description == b.description
+        && a.date == b.date
+        && a.intervals.len() == b.intervals.len()
+        && a.intervals
+            .iter()
+            .zip(b.intervals.iter())
+            .all(|(a, b)| a.start == b.start && a.end == b.end)
+}
+
+// Project of an entry read from another time tracker, created along with its client unless one
+// with the same names exists. Entries without a project name have no project.
+pub fn find_or_add_project(dataset: &mut Dataset, name: &str, client_name: &str) -> Option<i64> {
+    if name.is_empty() {
+        return None;
+    }
+    let client_id = if client_name.is_empty() {
+        None
+    } else {
+        Some(
+            match dataset.clients.iter().find(|c| c.name == client_name) {
+                Some(client) => client.id,
+                None => {
+                    let id = next_id(dataset.clients.iter().map(|c| c.id));
+                    dataset.clients.push(Client {
+                        id,
+                        name: client_name.to_string(),
+                    });
+                    id
+                }
+            },
+        )
+    };
+    let existing = dataset
+        .projects
+        .iter()
+        .find(|p| p.name == name && p.client_id == client_id);
+    let id = match existing {
+        Some(project) => project.id,
+        None => {
+            let id = next_id(dataset.projects.iter().map(|p| p.id));
+            dataset.projects.push(Project {
+                id,
+                name: name.to_string(),
+                client_id,
+                color: PROJECT_COLORS[dataset.projects.len() % PROJECT_COLORS.len()],
+            });
+            id
+        }
+    };
+    Some(id)
+}
+
+// An entry with a single session read from another time tracker, dated to the day it started.
+// Tags that only differed in case or spacing there are kept once.
+pub fn imported_entry(
+    description: String,
+    tags: Vec<String>,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    project_id: Option<i64>,
+) -> TrackedTime {
+    let mut tracked_time = TrackedTime::with_timestamps(
+        description,
+        vec![Interval::new(start, end, IntervalSource::Imported)],
+        start.with_timezone(&chrono::Local).date().naive_local(),
+        end,
+        end,
+    );
+    for tag in tags {
+        if !tracked_time.tags.contains(&tag) {
+            tracked_time.tags.push(tag);
+        }
+    }
+    tracked_time.project_id = project_id;
+    tracked_time
+}
+
+// Sessions of the same work on the same day become one entry, like when tracked here
+pub fn add_imported_session(
+    dataset: &mut Dataset,
+    description: String,
+    tags: Vec<String>,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    project_id: Option<i64>,
+) {
+    let entry = imported_entry(description, tags, start, end, project_id);
+    let existing = dataset.tracked_times.iter_mut().find(|t| {
+        t.description == entry.description
+            && t.tags == entry.tags
+            && t.project_id == entry.project_id
+            && t.date == entry.date
+    });
+    match existing {
+        Some(existing) => {
+            existing.intervals.extend(entry.intervals);
+            existing.intervals.sort_by_key(|interval| interval.start);
+            existing.modified_at = existing.modified_at.max(entry.modified_at);
+        }
+        None => dataset.tracked_times.push(entry),
+    }
+}
+
+// Other time trackers write "2021-06-01" or "2021/06/01" and "09:30:00" or "09:30" in local time
+pub fn parse_local(date: &str, time: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let date = date.replace('/', "-");
+    match time.matches(':').count() {
+        1 => model::parse_date_time(&format!("{} {}:00", date, time)),
+        _ => model::parse_date_time(&format!("{} {}", date, time)),
+    }
+}
+
+// Tags of other time trackers may contain spaces, tags here are single lowercase words
+pub fn imported_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join("-")
+        .to_lowercase()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    // Line of the file starting at 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+fn next_id(ids: impl Iterator<Item = i64>) -> i64 {
+    ids.max().unwrap_or(0) + 1
+}
+
+fn map_id(ids: &[(i64, i64)], id: i64) -> Option<i64> {
+    ids.iter()
+        .find(|(imported, _)| *imported == id)
+        .map(|(_, stored)| *stored)
+}
@@ -0,0 +1,468 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::model::{Client, Interval, IntervalSource, Project, TrackedTime};
+use crate::storage::Dataset;
+use crate::timer::{self, Timer};
+
+// Increased whenever a change to the format would be misread by older versions
+pub const FORMAT_VERSION: u64 = 1;
+const FORMAT_NAME: &str = "simple_time_tracker";
+
+#[derive(Debug)]
+pub enum JsonError {
+    Syntax(serde_json::Error),
+    // Valid JSON, but not written by this application
+    NotAnExport,
+    UnsupportedVersion(u64),
+    InvalidData(String),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonError::Syntax(error) => write!(f, "Invalid JSON: {}", error),
+            JsonError::NotAnExport => write!(f, "The file is not a Simple Time Tracker export"),
+            JsonError::UnsupportedVersion(version) => write!(
+                f,
+                "The file has format version {}, this version reads up to version {}",
+                version, FORMAT_VERSION
+            ),
+            JsonError::InvalidData(message) => write!(f, "Invalid data: {}", message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(error: serde_json::Error) -> Self {
+        JsonError::Syntax(error)
+    }
+}
+
+// The documented schema, see the README. Fields that may be left out have serde defaults.
+#[derive(Serialize, Deserialize)]
+struct Document {
+    format: String,
+    version: u64,
+    exported_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    settings: BTreeMap<String, i64>,
+    #[serde(default)]
+    clients: Vec<ClientJson>,
+    #[serde(default)]
+    projects: Vec<ProjectJson>,
+    #[serde(default)]
+    timers: Vec<TimerJson>,
+    #[serde(default)]
+    entries: Vec<EntryJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClientJson {
+    id: i64,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectJson {
+    id: i64,
+    name: String,
+    #[serde(default)]
+    client_id: Option<i64>,
+    // 0xRRGGBB
+    color: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimerJson {
+    #[serde(default)]
+    id: Option<i64>,
+    name: String,
+    // Set while the timer runs, otherwise the timer shows paused_seconds
+    #[serde(default)]
+    running_since: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    paused_seconds: i64,
+    #[serde(default)]
+    time_box_seconds: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryJson {
+    #[serde(default)]
+    id: Option<i64>,
+    description: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    project_id: Option<i64>,
+    date: chrono::NaiveDate,
+    created_at: chrono::DateTime<chrono::Utc>,
+    modified_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    planned_seconds: Option<i64>,
+    intervals: Vec<IntervalJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IntervalJson {
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    // timer, manual, legacy, pomodoro or imported
+    source: String,
+}
+
+pub fn export(dataset: &Dataset, clock: &dyn Clock) -> String {
+    let document = Document {
+        format: String::from(FORMAT_NAME),
+        version: FORMAT_VERSION,
+        exported_at: clock.now(),
+        settings: dataset.settings.iter().cloned().collect(),
+        clients: dataset
+            .clients
+            .iter()
+            .map(|client| ClientJson {
+                id: client.id,
+                name: client.name.clone(),
+            })
+            .collect(),
+        projects: dataset
+            .projects
+            .iter()
+            .map(|project| ProjectJson {
+                id: project.id,
+                name: project.name.clone(),
+                client_id: project.client_id,
+                color: project.color,
+            })
+            .collect(),
+        timers: dataset
+            .timers
+            .iter()
+            .map(|timer| TimerJson {
+                id: timer.id,
+                name: timer.name.clone(),
+                running_since: Some(timer.start_time).filter(|_| timer.is_running),
+                paused_seconds: match timer.is_running {
+                    true => 0,
+                    false => (timer.pause_time - timer.start_time).num_seconds(),
+                },
+                time_box_seconds: timer.time_box.map(|time_box| time_box.num_seconds()),
+            })
+            .collect(),
+        entries: dataset
+            .tracked_times
+            .iter()
+            .map(|tracked_time| EntryJson {
+                id: tracked_time.id,
+                description: tracked_time.description.clone(),
+                tags: tracked_time.tags.clone(),
+                project_id: tracked_time.project_id,
+                date: tracked_time.date,
+                created_at: tracked_time.created_at,
+                modified_at: tracked_time.modified_at,
+                planned_seconds: tracked_time.planned.map(|planned| planned.num_seconds()),
+                intervals: tracked_time
+                    .intervals
+                    .iter()
+                    .map(|interval| IntervalJson {
+                        start: interval.start,
+                        end: interval.end,
+                        source: String::from(interval.source.as_str()),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    // Serializing plain data to a string cannot fail
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+pub fn import(input: &str, clock: &dyn Clock) -> Result<Dataset, JsonError> {
+    // The version is checked first so newer files are not reported as malformed
+    let value: serde_json::Value = serde_json::from_str(input)?;
+    if value.get("format").and_then(|format| format.as_str()) != Some(FORMAT_NAME) {
+        return Err(JsonError::NotAnExport);
+    }
+    match value.get("version").and_then(|version| version.as_u64()) {
+        Some(FORMAT_VERSION) => {}
+        Some(version) => return Err(JsonError::UnsupportedVersion(version)),
+        None => return Err(JsonError::NotAnExport),
+    }
+    let document: Document = serde_json::from_value(value)?;
+    unique_ids("client", document.clients.iter().map(|client| client.id))?;
+    unique_ids(
+        "project",
+        document.projects.iter().map(|project| project.id),
+    )?;
+    unique_ids("timer", document.timers.iter().filter_map(|timer| timer.id))?;
+    unique_ids(
+        "entry",
+        document.entries.iter().filter_map(|entry| entry.id),
+    )?;
+
+    let mut timers = Vec::new();
+    for timer_json in document.timers {
+        let paused = seconds(
+            timer_json.paused_seconds,
+            "paused_seconds",
+            &timer_json.name,
+        )?;
+        let mut timer = match timer_json.running_since {
+            Some(running_since) => Timer::running_since(running_since, clock),
+            None => Timer::paused(paused, clock),
+        };
+        timer.time_box = timer_json
+            .time_box_seconds
+            .map(|time_box| seconds(time_box, "time_box_seconds", &timer_json.name))
+            .transpose()?;
+        timer.id = timer_json.id;
+        timer.name = timer_json.name;
+        timers.push(timer);
+    }
+    // Like a new database, there is always at least one timer
+    if timers.is_empty() {
+        timers.push(Timer::paused(chrono::Duration::zero(), clock));
+    }
+
+    let mut tracked_times = Vec::new();
+    for entry in document.entries {
+        let mut intervals = Vec::new();
+        for interval in entry.intervals {
+            if interval.end < interval.start {
+                return Err(JsonError::InvalidData(format!(
+                    "a session of '{}' ends before it starts",
+                    entry.description
+                )));
+            }
+            if interval.end - interval.start > chrono::Duration::seconds(timer::MAX_SECONDS) {
+                return Err(JsonError::InvalidData(format!(
+                    "a session of '{}' is too long",
+                    entry.description
+                )));
+            }
+            intervals.push(Interval::new(
+                interval.start,
+                interval.end,
+                IntervalSource::parse(&interval.source),
+            ));
+        }
+        let mut tracked_time = TrackedTime::with_timestamps(
+            entry.description,
+            intervals,
+            entry.date,
+            entry.created_at,
+            entry.modified_at,
+        );
+        tracked_time.id = entry.id;
+        tracked_time.tags = entry.tags;
+        tracked_time.project_id = entry.project_id;
+        tracked_time.planned = entry
+            .planned_seconds
+            .map(|planned| seconds(planned, "planned_seconds", &tracked_time.description))
+            .transpose()?;
+        tracked_times.push(tracked_time);
+    }
+
+    Ok(Dataset {
+        settings: document.settings.into_iter().collect(),
+        clients: document
+            .clients
+            .into_iter()
+            .map(|client| Client {
+                id: client.id,
+                name: client.name,
+            })
+            .collect(),
+        projects: document
+            .projects
+            .into_iter()
+            .map(|project| Project {
+                id: project.id,
+                name: project.name,
+                client_id: project.client_id,
+                color: project.color,
+            })
+            .collect(),
+        timers,
+        tracked_times,
+    })
+}
+
+// Durations of timers and entries can neither be negative nor too long to calculate with
+fn seconds(value: i64, field: &str, name: &str) -> Result<chrono::Duration, JsonError> {
+    if !(0..=timer::MAX_SECONDS).contains(&value) {
+        return Err(JsonError::InvalidData(format!(
+            "{} of '{}' is out of range",
+            field, name
+        )));
+    }
+    Ok(chrono::Duration::seconds(value))
+}
+
+// Replacing keeps the IDs of the file, a row given twice would overwrite the first one
+fn unique_ids(kind: &str, ids: impl Iterator<Item = i64>) -> Result<(), JsonError> {
+    let mut seen = BTreeSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            return Err(JsonError::InvalidData(format!(
+                "the {} ID {} is used more than once",
+                kind, id
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use chrono::{Duration, TimeZone};
+
+    fn clock() -> ManualClock {
+        ManualClock::new(chrono::Utc.ymd(2021, 6, 1).and_hms(18, 0, 0))
+    }
+
+    fn time(hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.ymd(2021, 6, 1).and_hms(hour, minute, 0)
+    }
+
+    fn dataset(clock: &ManualClock) -> Dataset {
+        let mut running = Timer::running_since(time(17, 0), clock);
+        running.id = Some(1);
+        running.name = String::from("Work");
+        running.time_box = Some(Duration::minutes(90));
+        let mut paused = Timer::paused(Duration::minutes(25), clock);
+        paused.id = Some(2);
+        paused.name = String::from("Reading");
+
+        let mut tracked_time = TrackedTime::new(
+            String::from("Fix login"),
+            vec![
+                Interval::new(time(9, 0), time(10, 0), IntervalSource::Timer),
+                Interval::new(time(11, 0), time(11, 30), IntervalSource::Imported),
+            ],
+            time(12, 0),
+        );
+        tracked_time.id = Some(5);
+        tracked_time.tags = vec![String::from("bug")];
+        tracked_time.project_id = Some(3);
+        tracked_time.planned = Some(Duration::hours(1));
+
+        Dataset {
+            settings: vec![(String::from("darkmode"), 1)],
+            clients: vec![Client {
+                id: 7,
+                name: String::from("Acme"),
+            }],
+            projects: vec![Project {
+                id: 3,
+                name: String::from("Website"),
+                client_id: Some(7),
+                color: 0x3a86ff,
+            }],
+            timers: vec![running, paused],
+            tracked_times: vec![tracked_time],
+        }
+    }
+
+    // An export of the dataset changed before it is read again
+    fn import_changed(change: impl FnOnce(&mut serde_json::Value)) -> Result<Dataset, JsonError> {
+        let clock = clock();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&export(&dataset(&clock), &clock)).unwrap();
+        change(&mut value);
+        import(&value.to_string(), &clock)
+    }
+
+    #[test]
+    fn reads_its_own_export() {
+        let clock = clock();
+        let original = dataset(&clock);
+        let imported = import(&export(&original, &clock), &clock).unwrap();
+
+        assert_eq!(imported.settings, original.settings);
+        assert_eq!(imported.clients[0].name, "Acme");
+        assert_eq!(imported.projects[0].client_id, Some(7));
+        assert_eq!(imported.projects[0].color, 0x3a86ff);
+
+        let running = &imported.timers[0];
+        assert_eq!(running.id, Some(1));
+        assert!(running.is_running);
+        assert_eq!(running.start_time, time(17, 0));
+        assert_eq!(running.time_box, Some(Duration::minutes(90)));
+        let paused = &imported.timers[1];
+        assert!(!paused.is_running);
+        assert_eq!(paused.get_current_duration(&clock), Duration::minutes(25));
+
+        assert_eq!(imported.tracked_times, original.tracked_times);
+    }
+
+    #[test]
+    fn rejects_other_files_and_versions() {
+        let clock = clock();
+        assert!(matches!(import("{", &clock), Err(JsonError::Syntax(_))));
+        assert!(matches!(
+            import("{\"format\": \"other\"}", &clock),
+            Err(JsonError::NotAnExport)
+        ));
+        assert!(matches!(
+            import_changed(|value| value["version"] = serde_json::json!(FORMAT_VERSION + 1)),
+            Err(JsonError::UnsupportedVersion(version)) if version == FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_ids_that_are_used_twice() {
+        let result = import_changed(|value| {
+            let entry = value["entries"][0].clone();
+            value["entries"].as_array_mut().unwrap().push(entry);
+        });
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+        let result = import_changed(|value| value["timers"][1]["id"] = serde_json::json!(1));
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+        let result = import_changed(|value| {
+            let project = value["projects"][0].clone();
+            value["projects"].as_array_mut().unwrap().push(project);
+        });
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+
+        // Entries without an ID get new ones
+        let result = import_changed(|value| {
+            value["entries"][0]["id"] = serde_json::Value::Null;
+            let entry = value["entries"][0].clone();
+            value["entries"].as_array_mut().unwrap().push(entry);
+        });
+        assert_eq!(result.unwrap().tracked_times.len(), 2);
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        let result = import_changed(|value| {
+            value["timers"][1]["paused_seconds"] = serde_json::json!(i64::MAX)
+        });
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+        let result =
+            import_changed(|value| value["timers"][0]["time_box_seconds"] = serde_json::json!(-1));
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+        let result = import_changed(|value| {
+            value["entries"][0]["planned_seconds"] = serde_json::json!(i64::MIN)
+        });
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+    }
+
+    #[test]
+    fn rejects_sessions_that_cannot_be_tracked() {
+        let result = import_changed(|value| {
+            value["entries"][0]["intervals"][0]["end"] = serde_json::json!("2021-06-01T08:00:00Z")
+        });
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+        let result = import_changed(|value| {
+            value["entries"][0]["intervals"][0]["end"] = serde_json::json!("2200-01-01T00:00:00Z")
+        });
+        assert!(matches!(result, Err(JsonError::InvalidData(_))));
+    }
+}
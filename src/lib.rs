@@ -0,0 +1,25 @@
+pub mod clock;
+pub mod csv;
+pub mod database;
+pub mod history;
+pub mod ics;
+pub mod import;
+pub mod json;
+mod migrations;
+pub mod model;
+pub mod pomodoro;
+pub mod storage;
+pub mod timeclock;
+pub mod timer;
+pub mod timewarrior;
+pub mod toggl;
+pub mod tracker;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use history::History;
+pub use import::{ImportMode, ImportSummary};
+pub use model::{Client, DateRange, Interval, IntervalSource, Project, TrackedTime};
+pub use pomodoro::{Phase, Pomodoro, PomodoroSettings};
+pub use storage::{Changes, Dataset, Storage};
+pub use timer::Timer;
+pub use tracker::{OperationError, Target, Tracker};
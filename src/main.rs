@@ -1,15 +1,32 @@
 #![windows_subsystem = "windows"]
-mod database;
+mod cli;
 mod style;
 
 use iced::{
-    button, executor, scrollable, text_input, time, tooltip, window, Application, Button,
-    Clipboard, Color, Column, Command, Container, Element, Length, Row, Rule, Scrollable, Settings,
-    Space, Subscription, Text, TextInput, Tooltip,
+    button, executor, pick_list, scrollable, text_input, time, tooltip, window, Application,
+    Button, Checkbox, Clipboard, Color, Column, Command, Container, Element, Length, PickList, Row,
+    Rule, Scrollable, Settings, Space, Subscription, Text, TextInput, Tooltip,
 };
-use iced_native::Event;
+use iced_native::{keyboard, Event};
+use simple_time_tracker::database::{self, SqliteStorage};
+use simple_time_tracker::model::{self, Interval, TrackedTime, PROJECT_COLORS};
+use simple_time_tracker::{csv, ics, import, json, timeclock, timer, timewarrior, toggl};
+use simple_time_tracker::{
+    Changes, Dataset, DateRange, History, ImportMode, ImportSummary, Phase, Pomodoro,
+    PomodoroSettings, Storage, SystemClock, Target, Tracker,
+};
+use std::fmt;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Instant;
 
 pub fn main() -> iced::Result {
+    let (location, args) = parse_arguments(std::env::args().skip(1).collect());
+    if !args.is_empty() {
+        attach_console();
+        std::process::exit(cli::run(&location, &args));
+    }
+
     SimpleTimeTracker::run(Settings {
         window: window::Settings {
             size: (700, 400),
@@ -17,20 +34,117 @@ pub fn main() -> iced::Result {
             ..window::Settings::default()
         },
         exit_on_close_request: false,
+        flags: location,
         ..Settings::default()
     })
 }
 
+// The windows subsystem starts without a console, commands write to the one they were run from
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = 0xFFFF_FFFF;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // Fails when not run from a console, there is nowhere to write to then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+pub struct DatabaseLocation {
+    path: PathBuf,
+    adopt_legacy: bool,
+}
+
+impl Default for DatabaseLocation {
+    fn default() -> Self {
+        DatabaseLocation {
+            path: database::default_path(),
+            adopt_legacy: true,
+        }
+    }
+}
+
+// Splits off the database location, the remaining arguments are a command line command.
+// The --db argument takes precedence over the environment variable and the default location.
+fn parse_arguments(args: Vec<String>) -> (DatabaseLocation, Vec<String>) {
+    let mut db_path = None;
+    let mut remaining = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--db" {
+            db_path = args.next().map(PathBuf::from);
+        } else if let Some(path) = arg.strip_prefix("--db=") {
+            db_path = Some(PathBuf::from(path));
+        } else {
+            remaining.push(arg);
+        }
+    }
+
+    let location = match db_path.or_else(|| match std::env::var_os(database::DATABASE_ENV_VAR) {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => None,
+    }) {
+        Some(path) => DatabaseLocation {
+            path,
+            adopt_legacy: false,
+        },
+        None => DatabaseLocation::default(),
+    };
+    (location, remaining)
+}
+
 struct SimpleTimeTracker {
+    storage: SqliteStorage,
+    adopt_legacy_database: bool,
     is_dark_mode: bool,
-    is_running: bool,
-    start_time: chrono::DateTime<chrono::Utc>,
-    pause_time: chrono::DateTime<chrono::Utc>,
-    tracked_times: Vec<TrackedTime>,
+    tracker: Tracker,
+    // Index of the timer that the timer buttons and the operations panel use
+    selected_timer: usize,
+    pomodoro: Pomodoro,
+    // The main display and its buttons show the pomodoro instead of the selected timer
+    is_pomodoro_mode: bool,
+    history: History,
+    toast: Option<Toast>,
+    // Names of the timers whose time box ran out and was already alerted
+    alerted_time_boxes: Vec<String>,
+    tracked_time_rows: Vec<TrackedTimeRow>,
+    project_delete_buttons: Vec<button::State>,
+    selected_project: Option<i64>,
+    tag_filter: Vec<String>,
+    is_loaded: bool,
+    storage_error: Option<StorageError>,
+    // IDs of removed entries and timers that are still to be deleted from the storage
+    deleted_tracked_times: Vec<i64>,
+    deleted_timers: Vec<i64>,
 
     should_exit: bool,
+    close_requested: bool,
     start_stop_button: button::State,
     clear_button: button::State,
+    timer_buttons: Vec<button::State>,
+    timer_name_text_input: text_input::State,
+    timer_name_input: String,
+    add_timer_button: button::State,
+    remove_timer_button: button::State,
+    time_box_text_input: text_input::State,
+    time_box_input: String,
+    pomodoro_button: button::State,
+    pomodoro_work_text_input: text_input::State,
+    pomodoro_work_input: String,
+    pomodoro_short_break_text_input: text_input::State,
+    pomodoro_short_break_input: String,
+    pomodoro_long_break_text_input: text_input::State,
+    pomodoro_long_break_input: String,
+    pomodoro_cycles_text_input: text_input::State,
+    pomodoro_cycles_input: String,
     dark_mode_button: button::State,
     time_text_input: text_input::State,
     time_input: String,
@@ -38,293 +152,1667 @@ struct SimpleTimeTracker {
     description_input: String,
     index_text_input: text_input::State,
     index_input: String,
+    index_suggestions_pick_list: pick_list::State<EntrySuggestion>,
+    target_index_text_input: text_input::State,
+    target_index_input: String,
+    target_index_suggestions_pick_list: pick_list::State<EntrySuggestion>,
+    operation: Operation,
+    operation_pick_list: pick_list::State<Operation>,
+    operation_error: Option<String>,
     apply_operation_button: button::State,
     tracked_times_scroll: scrollable::State,
+    retry_button: button::State,
+    dismiss_error_button: button::State,
+    project_pick_list: pick_list::State<ProjectOption>,
+    show_projects: bool,
+    projects_button: button::State,
+    project_name_text_input: text_input::State,
+    project_name_input: String,
+    client_name_text_input: text_input::State,
+    client_name_input: String,
+    add_project_button: button::State,
+    show_export: bool,
+    export_button: button::State,
+    export_format: ExportFormat,
+    export_format_pick_list: pick_list::State<ExportFormat>,
+    export_path_text_input: text_input::State,
+    export_path_input: String,
+    export_from_text_input: text_input::State,
+    export_from_input: String,
+    export_to_text_input: text_input::State,
+    export_to_input: String,
+    export_columns: Vec<csv::Column>,
+    export_file_button: button::State,
+    import_path_text_input: text_input::State,
+    import_path_input: String,
+    import_merge_button: button::State,
+    import_replace_button: button::State,
+    import_format: ImportFormat,
+    import_format_pick_list: pick_list::State<ImportFormat>,
+    import_preview_button: button::State,
+    // What a merge of the file would add, shown until the file or format changes
+    import_preview: Option<ImportSummary>,
+    tag_filter_buttons: Vec<button::State>,
+    clear_tag_filter_button: button::State,
+    undo_toast_button: button::State,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StorageOperation {
+    Load,
+    StoreSettings,
+    // Timers and entries
+    StoreChanges,
+    StoreProjects,
+}
+
+#[derive(Debug, Clone)]
+struct Toast {
+    message: String,
+    shown_at: Instant,
+    // Messages about a change offer to undo it
+    can_undo: bool,
 }
 
+#[derive(Debug)]
+struct StorageError {
+    operation: StorageOperation,
+    error: database::Error,
+}
+
+const DARKMODE_KEY: &str = "darkmode";
+const POMODORO_WORK_KEY: &str = "pomodoro_work_seconds";
+const POMODORO_SHORT_BREAK_KEY: &str = "pomodoro_short_break_seconds";
+const POMODORO_LONG_BREAK_KEY: &str = "pomodoro_long_break_seconds";
+const POMODORO_CYCLES_KEY: &str = "pomodoro_cycles";
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+// Entries listed before importing, the rest is counted
+const IMPORT_PREVIEW_LENGTH: usize = 10;
+
+// GUI state of an entry in the list, kept at the same index as the entry in the tracker
 #[derive(Debug, Clone)]
-pub struct TrackedTime {
-    description: String,
-    duration: chrono::Duration,
+struct TrackedTimeRow {
+    is_expanded: bool,
+    intervals: Vec<IntervalRow>,
+    is_editing: bool,
+    description_input: String,
+    duration_input: String,
+    edit_error: Option<String>,
 
+    description_text_input: text_input::State,
+    duration_text_input: text_input::State,
+    edit_button: button::State,
+    save_edit_button: button::State,
+    cancel_edit_button: button::State,
     copy_button: button::State,
     delete_button: button::State,
+    expand_button: button::State,
+    add_interval_button: button::State,
 }
 
-impl TrackedTime {
-    fn new(duration: chrono::Duration, description: String) -> Self {
-        TrackedTime {
-            description,
-            duration,
+impl TrackedTimeRow {
+    fn new() -> Self {
+        TrackedTimeRow {
+            is_expanded: false,
+            intervals: Vec::new(),
+            is_editing: false,
+            description_input: String::new(),
+            duration_input: String::new(),
+            edit_error: None,
+            description_text_input: text_input::State::new(),
+            duration_text_input: text_input::State::new(),
+            edit_button: button::State::new(),
+            save_edit_button: button::State::new(),
+            cancel_edit_button: button::State::new(),
             copy_button: button::State::new(),
             delete_button: button::State::new(),
+            expand_button: button::State::new(),
+            add_interval_button: button::State::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IntervalRow {
+    start_input: String,
+    end_input: String,
+    start_text_input: text_input::State,
+    end_text_input: text_input::State,
+    save_button: button::State,
+    delete_button: button::State,
+}
+
+impl IntervalRow {
+    fn new(interval: &Interval) -> Self {
+        IntervalRow {
+            start_input: model::format_date_time(interval.start),
+            end_input: model::format_date_time(interval.end),
+            start_text_input: text_input::State::new(),
+            end_text_input: text_input::State::new(),
+            save_button: button::State::new(),
+            delete_button: button::State::new(),
+        }
+    }
+
+    fn reset_inputs(&mut self, interval: &Interval) {
+        self.start_input = model::format_date_time(interval.start);
+        self.end_input = model::format_date_time(interval.end);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProjectOption {
+    id: Option<i64>,
+    label: String,
+}
+
+impl fmt::Display for ProjectOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+// Entry matching the description typed into an entry input
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EntrySuggestion {
+    number: usize,
+    label: String,
+}
+
+impl fmt::Display for EntrySuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PomodoroSetting {
+    Work,
+    ShortBreak,
+    LongBreak,
+    Cycles,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+    Timewarrior,
+    Timeclock,
+    Ics,
+}
+
+impl ExportFormat {
+    const ALL: [ExportFormat; 5] = [
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        ExportFormat::Timewarrior,
+        ExportFormat::Timeclock,
+        ExportFormat::Ics,
+    ];
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Timewarrior => "data",
+            ExportFormat::Timeclock => "timeclock",
+            ExportFormat::Ics => "ics",
         }
     }
 }
 
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ExportFormat::Csv => "CSV",
+                ExportFormat::Json => "JSON",
+                ExportFormat::Timewarrior => "Timewarrior",
+                ExportFormat::Timeclock => "Timeclock",
+                ExportFormat::Ics => "iCalendar",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportFormat {
+    Json,
+    Toggl,
+    Timewarrior,
+    Timeclock,
+}
+
+impl ImportFormat {
+    const ALL: [ImportFormat; 4] = [
+        ImportFormat::Json,
+        ImportFormat::Toggl,
+        ImportFormat::Timewarrior,
+        ImportFormat::Timeclock,
+    ];
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ImportFormat::Json => "JSON",
+                ImportFormat::Toggl => "Toggl CSV",
+                ImportFormat::Timewarrior => "Timewarrior",
+                ImportFormat::Timeclock => "Timeclock",
+            }
+        )
+    }
+}
+
+// What the operations panel does with the time input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Add,
+    Subtract,
+    Move,
+    Return,
+}
+
+impl Operation {
+    const ALL: [Operation; 4] = [
+        Operation::Add,
+        Operation::Subtract,
+        Operation::Move,
+        Operation::Return,
+    ];
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Operation::Add => "Add",
+                Operation::Subtract => "Subtract",
+                Operation::Move => "Move",
+                Operation::Return => "Return",
+            }
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 enum Message {
     EventOccurred(iced_native::Event),
     TimeUpdate,
     StartStopTimer,
     ClearTimer,
+    SelectTimer(usize),
+    TimerNameInputChanged(String),
+    AddTimer,
+    RemoveTimer,
+    TimeBoxInputChanged(String),
+    SetTimeBox,
+    TogglePomodoro,
+    PomodoroSettingChanged(PomodoroSetting, String),
     DarkModeToggle,
     TimeInputChanged(String),
     DescriptionInputChanged(String),
     IndexInputChanged(String),
+    TargetIndexInputChanged(String),
+    IndexSuggestionSelected(EntrySuggestion),
+    TargetIndexSuggestionSelected(EntrySuggestion),
+    OperationSelected(Operation),
     ApplyOperation,
     DeleteTrackedTime(usize),
     CopyText(usize),
+    ToggleIntervals(usize),
+    EditTrackedTime(usize),
+    EditDescriptionChanged(usize, String),
+    EditDurationChanged(usize, String),
+    SaveEdit(usize),
+    CancelEdit(usize),
+    AddInterval(usize),
+    IntervalStartChanged(usize, usize, String),
+    IntervalEndChanged(usize, usize, String),
+    SaveInterval(usize, usize),
+    DeleteInterval(usize, usize),
+    RetryStorage,
+    DismissStorageError,
+    ProjectSelected(ProjectOption),
+    ToggleProjects,
+    ProjectNameInputChanged(String),
+    ClientNameInputChanged(String),
+    AddProject,
+    DeleteProject(usize),
+    ToggleExport,
+    ExportFormatSelected(ExportFormat),
+    ExportPathInputChanged(String),
+    ExportFromInputChanged(String),
+    ExportToInputChanged(String),
+    ExportColumnToggled(csv::Column, bool),
+    Export,
+    ImportPathInputChanged(String),
+    ImportFormatSelected(ImportFormat),
+    PreviewImport,
+    Import(ImportMode),
+    ToggleTagFilter(String),
+    ClearTagFilter,
+    Undo,
 }
 
-impl SimpleTimeTracker {
-    fn apply_operation(&mut self) {
-        let timer = self.get_current_duration();
-        let mut duration = chrono::Duration::zero();
-
-        // Parse time input
-        if self.time_input.len() > 0 {
-            let parts = self.time_input.split(':').collect::<Vec<&str>>();
-            if parts.len() > 2 {
-                return;
-            }
+fn load_pomodoro_settings(storage: &impl Storage) -> database::Result<PomodoroSettings> {
+    let defaults = PomodoroSettings::default();
+    // Values out of range, e.g. from an imported file, are left at their defaults
+    let load_duration = |key, default: chrono::Duration| {
+        storage.load_setting(key).map(|seconds| {
+            seconds
+                .filter(|seconds| (1..=timer::MAX_SECONDS).contains(seconds))
+                .map_or(default, chrono::Duration::seconds)
+        })
+    };
+    Ok(PomodoroSettings {
+        work: load_duration(POMODORO_WORK_KEY, defaults.work)?,
+        short_break: load_duration(POMODORO_SHORT_BREAK_KEY, defaults.short_break)?,
+        long_break: load_duration(POMODORO_LONG_BREAK_KEY, defaults.long_break)?,
+        cycles: storage
+            .load_setting(POMODORO_CYCLES_KEY)?
+            .filter(|cycles| (1..=u32::MAX as i64).contains(cycles))
+            .map_or(defaults.cycles, |cycles| cycles as u32),
+    })
+}
 
-            // Parse minutes
-            let minutes = if parts.len() == 2 {
-                parts[1].parse()
-            } else {
-                parts[0].parse()
-            };
-            if let Ok(m) = minutes {
-                if m >= 60 || (timer.num_hours() < 1 && m > timer.num_minutes()) {
-                    return;
+// Lengths and cycles must not be zero
+fn parse_pomodoro_length(input: &str) -> Option<chrono::Duration> {
+    timer::parse_length(input)
+        .ok()
+        .filter(|duration| *duration > chrono::Duration::zero())
+}
+
+fn parse_pomodoro_cycles(input: &str) -> Option<u32> {
+    input
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|cycles| *cycles > 0)
+}
+
+// Whole minutes are shown like the time input takes them, e.g. "25"
+fn format_setting_duration(duration: chrono::Duration) -> String {
+    if duration.num_seconds() % 60 == 0 {
+        duration.num_minutes().to_string()
+    } else {
+        format!("{}s", duration.num_seconds())
+    }
+}
+
+// Desktop notification for alerts that should be seen while the window is in the background,
+// it is left out where the notification tool is missing
+fn notify(message: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title \"Simple Time Tracker\"",
+            message
+        ));
+        command
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.arg("Simple Time Tracker").arg(message);
+        command
+    };
+    std::thread::spawn(move || {
+        let _ = command.status();
+    });
+}
+
+// Countdown of a time box as shown in the main display, "+" marks time over the box
+fn format_time_box(remaining: chrono::Duration) -> (String, String) {
+    let (sign, duration) = match remaining < chrono::Duration::zero() {
+        true => ("+", -remaining),
+        false => ("", remaining),
+    };
+    (
+        format!(
+            "{}{}:{:02}",
+            sign,
+            duration.num_hours(),
+            duration.num_minutes() % 60
+        ),
+        format!(":{:02}", duration.num_seconds() % 60),
+    )
+}
+
+// Exports are written next to the user's documents unless another path is entered
+fn default_export_path(extension: &str) -> String {
+    let directory = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    directory
+        .join(format!("simple_time_tracker.{}", extension))
+        .display()
+        .to_string()
+}
+
+// Empty inputs leave the range open on that side
+fn parse_date_range(from: &str, to: &str) -> Result<DateRange, String> {
+    let parse = |input: &str| match input.trim() {
+        "" => Ok(None),
+        input => model::parse_date(input)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid date '{}', use YYYY-MM-DD", input)),
+    };
+    Ok(DateRange {
+        from: parse(from)?,
+        to: parse(to)?,
+    })
+}
+
+// Returns the number of the entry as shown in the list
+fn find_entry(tracker: &Tracker, input: &str) -> Result<usize, String> {
+    if input.trim().is_empty() {
+        return Err(String::from(
+            "Enter the number, the @ID or a part of the description of an entry",
+        ));
+    }
+    tracker.find_entry(input).map_err(|error| error.to_string())
+}
+
+// Shown next to an entry input while it contains a description
+fn suggestion_pick_list<'a>(
+    state: &'a mut pick_list::State<EntrySuggestion>,
+    suggestions: Vec<EntrySuggestion>,
+    on_selected: fn(EntrySuggestion) -> Message,
+    is_dark_mode: bool,
+) -> Element<'a, Message> {
+    if suggestions.is_empty() {
+        return Space::with_width(Length::Shrink).into();
+    }
+    let summary = EntrySuggestion {
+        number: 0,
+        label: format!("{} found", suggestions.len()),
+    };
+    Row::new()
+        .push(Space::with_width(Length::Units(4)))
+        .push(
+            Container::new(
+                PickList::new(state, suggestions, Some(summary), on_selected)
+                    .padding(3)
+                    .text_size(16)
+                    .width(Length::Units(130))
+                    .style(style::PickListStyle { is_dark_mode }),
+            )
+            .height(Length::Fill)
+            .center_y(),
+        )
+        .into()
+}
+
+impl SimpleTimeTracker {
+    // Returns the indices of the entries that were changed
+    fn apply_operation(&mut self) -> Result<Vec<usize>, String> {
+        let changed = match self.operation {
+            Operation::Add => {
+                // Ensure only either description or index is set
+                if self.description_input.is_empty() == self.index_input.is_empty() {
+                    return Err(String::from(
+                        "Enter either a description or the number of an entry",
+                    ));
                 }
-                duration = duration.checked_add(&chrono::Duration::minutes(m)).unwrap();
-            } else {
-                return;
-            }
 
-            // Parse hours
-            if parts.len() == 2 {
-                let hours = parts[0].parse();
-                if let Ok(h) = hours {
-                    if h > timer.num_hours() {
-                        return;
+                let target = if !self.description_input.is_empty() {
+                    Target::New {
+                        description: self.description_input.clone(),
+                        project_id: self.selected_project,
                     }
-                    duration = duration.checked_add(&chrono::Duration::hours(h)).unwrap();
                 } else {
-                    return;
-                }
+                    Target::Existing(find_entry(&self.tracker, &self.index_input)?)
+                };
+                self.tracker
+                    .apply_operation(self.selected_timer, &self.time_input, target)
+                    .map(|index| vec![index])
             }
-        } else {
-            duration = timer;
-        }
-
-        // Ensure only either description or index is set
-        if (self.description_input.len() > 0) == (self.index_input.len() > 0) {
-            return;
+            Operation::Subtract => self
+                .tracker
+                .subtract(
+                    find_entry(&self.tracker, &self.index_input)?,
+                    &self.time_input,
+                )
+                .map(|index| vec![index]),
+            Operation::Move => self
+                .tracker
+                .transfer(
+                    find_entry(&self.tracker, &self.index_input)?,
+                    find_entry(&self.tracker, &self.target_index_input)?,
+                    &self.time_input,
+                )
+                .map(|(from, to)| vec![from, to]),
+            Operation::Return => self
+                .tracker
+                .return_to_timer(
+                    find_entry(&self.tracker, &self.index_input)?,
+                    self.selected_timer,
+                    &self.time_input,
+                )
+                .map(|index| vec![index]),
         }
+        .map_err(|error| error.to_string())?;
 
-        if self.description_input.len() > 0 {
-            self.tracked_times
-                .push(TrackedTime::new(duration, self.description_input.clone()));
-        } else {
-            let index = self.index_input.parse::<usize>().unwrap();
-            if index == 0 || index > self.tracked_times.len() {
-                return;
-            }
-
-            self.tracked_times[index - 1].duration = match self.tracked_times[index - 1]
-                .duration
-                .checked_add(&duration)
-            {
-                Some(d) => d,
-                None => return,
-            };
-        }
         self.time_input.clear();
         self.description_input.clear();
         self.index_input.clear();
-        if self.is_running {
-            self.start_time = self.start_time + duration;
-        } else {
-            self.pause_time = self.pause_time - duration;
+        self.target_index_input.clear();
+        // Sessions may have been split or reordered
+        for &i in changed.iter() {
+            if let Some(row) = self.tracked_time_rows.get_mut(i) {
+                row.intervals.clear();
+            }
         }
+        self.update_rows();
+        Ok(changed)
     }
 
-    fn get_current_duration(&self) -> chrono::Duration {
-        match self.is_running {
-            true => chrono::Utc::now() - self.start_time,
-            false => self.pause_time - self.start_time,
+    // The time that the time input refers to, unknown until an existing entry is entered
+    fn available_duration(&self) -> Option<chrono::Duration> {
+        match self.operation {
+            Operation::Add => Some(self.tracker.current_duration(self.selected_timer)),
+            _ => {
+                let number = self.tracker.find_entry(&self.index_input).ok()?;
+                Some(self.tracker.tracked_times[number - 1].duration())
+            }
         }
     }
-}
 
-impl Application for SimpleTimeTracker {
-    type Executor = executor::Default;
-    type Message = Message;
-    type Flags = ();
+    // Entries matching a description in an entry input, numbers and IDs are not looked up
+    fn entry_suggestions(&self, input: &str) -> Vec<EntrySuggestion> {
+        let input = input.trim();
+        if input.parse::<usize>().is_ok() || input.starts_with('@') {
+            return Vec::new();
+        }
+        self.tracker
+            .matching_entries(input)
+            .into_iter()
+            .take(10)
+            .map(|index| {
+                let description = &self.tracker.tracked_times[index].description;
+                let mut label = format!("{}: {}", index + 1, description);
+                if label.chars().count() > 18 {
+                    label = label.chars().take(17).collect::<String>() + "…";
+                }
+                EntrySuggestion {
+                    number: index + 1,
+                    label,
+                }
+            })
+            .collect()
+    }
 
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        let states = database::load_states();
-        let tracked_times = database::load_tracked_times();
+    fn load(&mut self) {
+        let result = self
+            .adopt_legacy_database()
+            .and_then(|_| Tracker::load(&self.storage, Rc::new(SystemClock)))
+            .and_then(|tracker| {
+                self.storage
+                    .load_setting(DARKMODE_KEY)
+                    .map(|dark_mode| (tracker, dark_mode))
+            })
+            .and_then(|(tracker, dark_mode)| {
+                load_pomodoro_settings(&self.storage)
+                    .map(|pomodoro_settings| (tracker, dark_mode, pomodoro_settings))
+            });
+        let (tracker, dark_mode, pomodoro_settings) = match result {
+            Ok(data) => data,
+            Err(error) => {
+                self.storage_error = Some(StorageError {
+                    operation: StorageOperation::Load,
+                    error,
+                });
+                return;
+            }
+        };
 
-        let is_running = if states.contains_key(database::PAUSED_KEY) {
-            states[database::PAUSED_KEY] == 0
-        } else {
-            false
+        self.is_dark_mode = match dark_mode {
+            Some(dark_mode) => dark_mode == 1,
+            None => true,
         };
+        self.tracker = tracker;
+        self.set_pomodoro_settings(pomodoro_settings);
+        self.history.clear();
+        self.deleted_tracked_times.clear();
+        self.deleted_timers.clear();
+        self.tracked_time_rows.clear();
+        self.update_rows();
+        self.is_loaded = true;
+        self.storage_error = None;
+    }
 
-        let start_time = if states.contains_key(database::TIME_KEY) {
-            if is_running {
-                chrono::DateTime::from_utc(
-                    chrono::NaiveDateTime::from_timestamp(states[database::TIME_KEY].into(), 0),
-                    chrono::Utc,
-                )
-            } else {
-                chrono::Utc::now() - chrono::Duration::seconds(states[database::TIME_KEY].into())
+    fn set_pomodoro_settings(&mut self, settings: PomodoroSettings) {
+        self.pomodoro_work_input = format_setting_duration(settings.work);
+        self.pomodoro_short_break_input = format_setting_duration(settings.short_break);
+        self.pomodoro_long_break_input = format_setting_duration(settings.long_break);
+        self.pomodoro_cycles_input = settings.cycles.to_string();
+        self.pomodoro.settings = settings;
+    }
+
+    // Applies and stores a setting once its input is valid
+    fn change_pomodoro_setting(&mut self, setting: PomodoroSetting, input: String) {
+        let settings = &mut self.pomodoro.settings;
+        let changed = match setting {
+            PomodoroSetting::Work => {
+                let work = parse_pomodoro_length(&input);
+                self.pomodoro_work_input = input;
+                work.map(|work| {
+                    settings.work = work;
+                    (POMODORO_WORK_KEY, work.num_seconds())
+                })
+            }
+            PomodoroSetting::ShortBreak => {
+                let short_break = parse_pomodoro_length(&input);
+                self.pomodoro_short_break_input = input;
+                short_break.map(|short_break| {
+                    settings.short_break = short_break;
+                    (POMODORO_SHORT_BREAK_KEY, short_break.num_seconds())
+                })
+            }
+            PomodoroSetting::LongBreak => {
+                let long_break = parse_pomodoro_length(&input);
+                self.pomodoro_long_break_input = input;
+                long_break.map(|long_break| {
+                    settings.long_break = long_break;
+                    (POMODORO_LONG_BREAK_KEY, long_break.num_seconds())
+                })
+            }
+            PomodoroSetting::Cycles => {
+                let cycles = parse_pomodoro_cycles(&input);
+                self.pomodoro_cycles_input = input;
+                cycles.map(|cycles| {
+                    settings.cycles = cycles;
+                    (POMODORO_CYCLES_KEY, cycles as i64)
+                })
             }
-        } else {
-            chrono::Utc::now()
         };
 
-        (
-            Self {
-                is_dark_mode: if states.contains_key(database::DARKMODE_KEY) {
-                    states[database::DARKMODE_KEY] == 1
-                } else {
-                    true
-                },
-                is_running,
-                start_time,
-                pause_time: chrono::Utc::now(),
-                tracked_times,
-
-                should_exit: false,
-                start_stop_button: button::State::new(),
-                clear_button: button::State::new(),
-                dark_mode_button: button::State::new(),
-                time_text_input: text_input::State::new(),
-                time_input: String::new(),
-                description_text_input: text_input::State::new(),
-                description_input: String::new(),
-                index_text_input: text_input::State::new(),
-                index_input: String::new(),
-                apply_operation_button: button::State::new(),
-                tracked_times_scroll: scrollable::State::new(),
-            },
-            Command::none(),
-        )
+        if let Some((key, value)) = changed {
+            if self.is_loaded {
+                let result = self.storage.store_setting(key, value);
+                self.handle_storage_result(StorageOperation::StoreSettings, result);
+            }
+        }
     }
 
-    fn title(&self) -> String {
-        String::from("Simple Time Tracker")
+    fn has_invalid_pomodoro_settings(&self) -> bool {
+        [
+            &self.pomodoro_work_input,
+            &self.pomodoro_short_break_input,
+            &self.pomodoro_long_break_input,
+        ]
+        .iter()
+        .any(|input| parse_pomodoro_length(input).is_none())
+            || parse_pomodoro_cycles(&self.pomodoro_cycles_input).is_none()
     }
 
-    fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
-        match message {
-            Message::EventOccurred(event) => {
-                if let Event::Window(iced_native::window::Event::CloseRequested) = event {
-                    self.store_state();
-                    self.should_exit = true;
-                }
+    // Completed work cycles become entries named like the description in the operations panel
+    fn log_pomodoro_sessions(&mut self) {
+        let sessions = self.pomodoro.advance(self.tracker.clock.as_ref());
+        if sessions.is_empty() {
+            return;
+        }
+        let description = match model::parse_tags(&self.description_input).0.is_empty() {
+            true => format!("Pomodoro {}", self.description_input.trim()),
+            false => self.description_input.clone(),
+        };
+        self.history.record(&self.tracker);
+        for session in sessions {
+            if let Ok(i) =
+                self.tracker
+                    .add_entry(&description, self.selected_project, vec![session])
+            {
+                self.update_rows();
+                self.save_tracked_time(i);
             }
-            Message::TimeUpdate => {}
-            Message::StartStopTimer => {
-                if self.is_running {
-                    self.pause_time = chrono::Utc::now();
-                } else {
-                    self.start_time = self.start_time + (chrono::Utc::now() - self.pause_time);
-                }
-                self.is_running = !self.is_running;
+        }
+    }
+
+    fn load_projects(&mut self) -> database::Result<()> {
+        self.tracker.load_projects(&self.storage)?;
+        self.update_rows();
+        Ok(())
+    }
+
+    // Adds rows for appended entries and sessions, removed ones are dropped where they are removed
+    fn update_rows(&mut self) {
+        let tracked_times = &self.tracker.tracked_times;
+        self.tracked_time_rows
+            .resize_with(tracked_times.len(), TrackedTimeRow::new);
+        for (tracked_time, row) in tracked_times.iter().zip(self.tracked_time_rows.iter_mut()) {
+            row.intervals.truncate(tracked_time.intervals.len());
+            for interval in tracked_time.intervals[row.intervals.len()..].iter() {
+                row.intervals.push(IntervalRow::new(interval));
             }
-            Message::ClearTimer => {
-                self.start_time = chrono::Utc::now();
-                self.pause_time = chrono::Utc::now();
+        }
+
+        self.timer_buttons
+            .resize_with(self.tracker.timers.len(), button::State::new);
+        self.selected_timer = self.selected_timer.min(self.tracker.timers.len() - 1);
+
+        self.project_delete_buttons
+            .resize_with(self.tracker.projects.len(), button::State::new);
+        if let Some(id) = self.selected_project {
+            if self.tracker.project(id).is_none() {
+                self.selected_project = None;
             }
-            Message::DarkModeToggle => self.is_dark_mode = !self.is_dark_mode,
-            Message::TimeInputChanged(input) => {
-                if input.len() < 3 {
-                    if input.chars().all(|c| c == ':' || c.is_numeric()) {
-                        self.time_input = input;
-                    }
-                } else if input.len() < 6 {
-                    if input.chars().all(|c| c == ':' || c.is_numeric())
-                        && input.chars().any(|c| c == ':')
-                    {
-                        self.time_input = input;
-                    }
-                }
+        }
+    }
+
+    fn undo(&mut self) {
+        let timers = self.tracker.timers.clone();
+        let tracked_times = self.tracker.tracked_times.clone();
+        if self.history.undo(&mut self.tracker) {
+            self.show_restored_state(timers, tracked_times);
+        }
+    }
+
+    fn redo(&mut self) {
+        let timers = self.tracker.timers.clone();
+        let tracked_times = self.tracker.tracked_times.clone();
+        if self.history.redo(&mut self.tracker) {
+            self.show_restored_state(timers, tracked_times);
+        }
+    }
+
+    // Entries may have been added or removed anywhere, so all rows are rebuilt, only the timers
+    // and entries that differ from before are stored
+    fn show_restored_state(&mut self, timers: Vec<timer::Timer>, tracked_times: Vec<TrackedTime>) {
+        self.toast = None;
+        self.tracked_time_rows.clear();
+        self.update_rows();
+        let tags = self.tracker.tags();
+        self.tag_filter.retain(|tag| tags.contains(tag));
+
+        let restored_timers = &self.tracker.timers;
+        let restored_tracked_times = &self.tracker.tracked_times;
+        self.deleted_timers.extend(
+            timers
+                .iter()
+                .filter_map(|timer| timer.id)
+                .filter(|&id| restored_timers.iter().all(|timer| timer.id != Some(id))),
+        );
+        self.deleted_tracked_times.extend(
+            tracked_times
+                .iter()
+                .filter_map(|tracked_time| tracked_time.id)
+                .filter(|&id| {
+                    restored_tracked_times
+                        .iter()
+                        .all(|tracked_time| tracked_time.id != Some(id))
+                }),
+        );
+        let changed_timers = (0..restored_timers.len())
+            .filter(|&i| !timers.contains(&restored_timers[i]))
+            .collect::<Vec<usize>>();
+        let changed_tracked_times = (0..restored_tracked_times.len())
+            .filter(|&i| !tracked_times.contains(&restored_tracked_times[i]))
+            .collect::<Vec<usize>>();
+        self.save_changes(&changed_tracked_times, &changed_timers);
+    }
+
+    // Undo and redo keys are left to the text input then
+    fn is_text_input_focused(&self) -> bool {
+        let text_inputs = [
+            &self.timer_name_text_input,
+            &self.time_box_text_input,
+            &self.pomodoro_work_text_input,
+            &self.pomodoro_short_break_text_input,
+            &self.pomodoro_long_break_text_input,
+            &self.pomodoro_cycles_text_input,
+            &self.time_text_input,
+            &self.description_text_input,
+            &self.index_text_input,
+            &self.target_index_text_input,
+            &self.project_name_text_input,
+            &self.client_name_text_input,
+            &self.export_path_text_input,
+            &self.export_from_text_input,
+            &self.export_to_text_input,
+            &self.import_path_text_input,
+        ];
+        text_inputs.iter().any(|text_input| text_input.is_focused())
+            || self.tracked_time_rows.iter().any(|row| {
+                row.description_text_input.is_focused()
+                    || row.duration_text_input.is_focused()
+                    || row.intervals.iter().any(|interval| {
+                        interval.start_text_input.is_focused()
+                            || interval.end_text_input.is_focused()
+                    })
+            })
+    }
+
+    fn show_toast(&mut self, message: String) {
+        self.toast = Some(Toast {
+            message,
+            shown_at: Instant::now(),
+            can_undo: true,
+        });
+    }
+
+    fn show_alert(&mut self, message: String) {
+        self.toast = Some(Toast {
+            message,
+            shown_at: Instant::now(),
+            can_undo: false,
+        });
+    }
+
+    // Alerts once when a time box runs out, a timer is alerted again after it was back within its box
+    fn alert_elapsed_time_boxes(&mut self) {
+        let clock = self.tracker.clock.as_ref();
+        let overrun = self
+            .tracker
+            .timers
+            .iter()
+            .filter(|timer| timer.is_overrun(clock))
+            .map(|timer| timer.name.clone())
+            .collect::<Vec<String>>();
+        for name in overrun.iter() {
+            if !self.alerted_time_boxes.contains(name) {
+                let message = format!("The time box of {} is over", name);
+                notify(&message);
+                self.show_alert(message);
             }
-            Message::DescriptionInputChanged(input) => self.description_input = input,
-            Message::IndexInputChanged(input) => {
-                if input.len() == 0 || (input.len() < 3 && input.parse::<usize>().is_ok()) {
-                    self.index_input = input
+        }
+        self.alerted_time_boxes = overrun;
+    }
+
+    // "45m for code review" also sets the description the time is saved with
+    fn set_time_box(&mut self) {
+        let input = self.time_box_input.clone();
+        let (length, description) = match input.split_once(" for ") {
+            Some((length, description)) => (length, Some(description.trim())),
+            None => (input.as_str(), None),
+        };
+        self.history.record(&self.tracker);
+        match self.tracker.set_time_box(self.selected_timer, length) {
+            Ok(()) => {
+                if let Some(description) = description {
+                    self.description_input = description.to_string();
                 }
+                self.time_box_input.clear();
+                self.save_timer(self.selected_timer);
             }
-            Message::ApplyOperation => {
-                self.apply_operation();
-                self.store_tracked_times();
+            Err(error) => {
+                self.history.discard();
+                self.show_alert(format!("Invalid time box: {}", error));
             }
-            Message::DeleteTrackedTime(i) => {
-                self.tracked_times.remove(i);
-                self.store_tracked_times();
+        }
+    }
+
+    fn export(&mut self) {
+        // JSON exports are read from the storage, which has to include every change
+        if self.export_format == ExportFormat::Json && !self.save_unsaved() {
+            return;
+        }
+        let path = self.export_path_input.trim().to_string();
+        let result = self
+            .export_content()
+            .and_then(|content| std::fs::write(&path, content).map_err(|error| error.to_string()));
+        match result {
+            Ok(()) => self.show_alert(format!("Exported to {}", path)),
+            Err(error) => self.show_alert(format!("Could not export: {}", error)),
+        }
+    }
+
+    fn export_content(&self) -> Result<String, String> {
+        match self.export_format {
+            ExportFormat::Csv => {
+                // Columns keep their usual order whatever order they were checked in
+                let columns = csv::Column::ALL
+                    .iter()
+                    .copied()
+                    .filter(|column| self.export_columns.contains(column))
+                    .collect::<Vec<csv::Column>>();
+                let range = parse_date_range(&self.export_from_input, &self.export_to_input)?;
+                Ok(csv::export(&self.tracker, &columns, &range))
             }
-            Message::CopyText(i) => {
-                clipboard.write(self.tracked_times[i].description.clone());
-                self.store_tracked_times();
+            ExportFormat::Json => Dataset::load(&self.storage, &SystemClock)
+                .map(|dataset| json::export(&dataset, &SystemClock))
+                .map_err(|error| error.to_string()),
+            ExportFormat::Timewarrior => {
+                let range = parse_date_range(&self.export_from_input, &self.export_to_input)?;
+                Ok(timewarrior::export(&self.tracker, &range))
+            }
+            ExportFormat::Timeclock => {
+                let range = parse_date_range(&self.export_from_input, &self.export_to_input)?;
+                Ok(timeclock::export(&self.tracker, &range))
+            }
+            ExportFormat::Ics => {
+                let range = parse_date_range(&self.export_from_input, &self.export_to_input)?;
+                Ok(ics::export(&self.tracker, &range, &SystemClock))
             }
         }
+    }
 
-        Command::none()
+    // Everything is loaded again afterwards, changes before the import can no longer be undone
+    fn read_import(&self) -> Result<Dataset, String> {
+        let input = std::fs::read_to_string(self.import_path_input.trim())
+            .map_err(|error| error.to_string())?;
+        match self.import_format {
+            ImportFormat::Json => json::import(&input, &SystemClock).map_err(|e| e.to_string()),
+            ImportFormat::Toggl => toggl::import(&input).map_err(|e| e.to_string()),
+            ImportFormat::Timewarrior => timewarrior::import(&input).map_err(|e| e.to_string()),
+            ImportFormat::Timeclock => timeclock::import(&input).map_err(|e| e.to_string()),
+        }
     }
 
-    fn subscription(&self) -> Subscription<Message> {
-        let mut subscriptions = Vec::new();
-        subscriptions.push(iced_native::subscription::events().map(Message::EventOccurred));
-        if self.is_running {
-            subscriptions.push(
-                time::every(std::time::Duration::from_millis(500)).map(|_| Message::TimeUpdate),
-            );
+    fn preview_import(&mut self) {
+        if !self.is_loaded || !self.save_unsaved() {
+            return;
+        }
+        let result = self.read_import().and_then(|dataset| {
+            import::import(
+                &self.storage,
+                dataset,
+                ImportMode::Merge,
+                &SystemClock,
+                true,
+            )
+            .map_err(|error| error.to_string())
+        });
+        match result {
+            Ok(summary) => self.import_preview = Some(summary),
+            Err(error) => self.show_alert(format!("Could not read the file: {}", error)),
         }
-        return Subscription::batch(subscriptions);
     }
 
-    fn should_exit(&self) -> bool {
-        self.should_exit
+    fn import(&mut self, mode: ImportMode) {
+        if !self.is_loaded || !self.save_unsaved() {
+            return;
+        }
+        let result = self.read_import().and_then(|dataset| {
+            import::import(&self.storage, dataset, mode, &SystemClock, false)
+                .map_err(|error| error.to_string())
+        });
+        match result {
+            Ok(summary) => {
+                self.load();
+                self.import_path_input.clear();
+                self.import_preview = None;
+                self.show_alert(match mode {
+                    ImportMode::Merge => format!(
+                        "Added {} entries, {} were already stored",
+                        summary.added.len(),
+                        summary.duplicates
+                    ),
+                    ImportMode::Replace => format!("Replaced with {} entries", summary.added.len()),
+                });
+            }
+            Err(error) => self.show_alert(format!("Could not import: {}", error)),
+        }
     }
 
-    fn view(&mut self) -> Element<Message> {
-        let duration = self.get_current_duration();
-        let time = Row::new()
-            .push(
-                Container::new(
-                    Text::new(format!(
-                        "{}:{:02}",
-                        duration.num_hours(),
-                        duration.num_minutes() % 60
-                    ))
-                    .size(60),
-                )
-                .style(style::TimerStyle {
+    fn project_options(&self) -> Vec<ProjectOption> {
+        let mut options = vec![ProjectOption {
+            id: None,
+            label: String::from("No project"),
+        }];
+        for project in self.tracker.projects.iter() {
+            let client = project.client_id.and_then(|id| self.tracker.client(id));
+            options.push(ProjectOption {
+                id: Some(project.id),
+                label: match client {
+                    Some(client) => format!("{} ({})", project.name, client.name),
+                    None => project.name.clone(),
+                },
+            });
+        }
+        options
+    }
+
+    fn adopt_legacy_database(&self) -> database::Result<()> {
+        if self.adopt_legacy_database {
+            database::adopt_legacy_database(self.storage.path())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Settings are written one by one as they change, this writes all of them again
+    fn save_settings(&mut self) -> bool {
+        if !self.is_loaded {
+            return false;
+        }
+        let settings = &self.pomodoro.settings;
+        let values = [
+            (DARKMODE_KEY, self.is_dark_mode as i64),
+            (POMODORO_WORK_KEY, settings.work.num_seconds()),
+            (POMODORO_SHORT_BREAK_KEY, settings.short_break.num_seconds()),
+            (POMODORO_LONG_BREAK_KEY, settings.long_break.num_seconds()),
+            (POMODORO_CYCLES_KEY, settings.cycles as i64),
+        ];
+        let result = values
+            .iter()
+            .try_for_each(|&(key, value)| self.storage.store_setting(key, value));
+        self.handle_storage_result(StorageOperation::StoreSettings, result)
+    }
+
+    fn save_timer(&mut self, index: usize) -> bool {
+        self.save_changes(&[], &[index])
+    }
+
+    fn save_tracked_time(&mut self, index: usize) -> bool {
+        self.save_changes(&[index], &[])
+    }
+
+    // Only the entries and timers that were changed here are written, together with pending
+    // deletions, so changes made meanwhile from the command line are kept
+    fn save_changes(&mut self, tracked_times: &[usize], timers: &[usize]) -> bool {
+        // Never overwrite stored data with the defaults shown after a failed load
+        if !self.is_loaded {
+            return false;
+        }
+        // It is unknown which changes before a failed write were stored, so all rows are written
+        let (tracked_times, timers) = match self.has_unsaved_changes() {
+            true => (
+                (0..self.tracker.tracked_times.len()).collect(),
+                (0..self.tracker.timers.len()).collect(),
+            ),
+            false => (tracked_times.to_vec(), timers.to_vec()),
+        };
+        let deleted_tracked_times = self.deleted_tracked_times.clone();
+        let deleted_timers = self.deleted_timers.clone();
+        let result = self.storage.save_changes(Changes {
+            deleted_tracked_times,
+            deleted_timers,
+            ..self.tracker.changes(&tracked_times, &timers)
+        });
+        if result.is_ok() {
+            self.deleted_tracked_times.clear();
+            self.deleted_timers.clear();
+        }
+        self.handle_storage_result(StorageOperation::StoreChanges, result)
+    }
+
+    fn delete_tracked_time(&mut self, index: usize) -> bool {
+        let tracked_time = self.tracker.tracked_times.remove(index);
+        self.tracked_time_rows.remove(index);
+        self.deleted_tracked_times.extend(tracked_time.id);
+        self.save_changes(&[], &[])
+    }
+
+    fn has_unsaved_changes(&self) -> bool {
+        match &self.storage_error {
+            Some(storage_error) => storage_error.operation == StorageOperation::StoreChanges,
+            None => false,
+        }
+    }
+
+    // Writes again what could not be stored, true when nothing is left unsaved
+    fn save_unsaved(&mut self) -> bool {
+        match self.storage_error.as_ref().map(|error| error.operation) {
+            Some(StorageOperation::StoreSettings) => self.save_settings(),
+            Some(StorageOperation::StoreChanges) => self.save_changes(&[], &[]),
+            _ => true,
+        }
+    }
+
+    fn handle_storage_result(
+        &mut self,
+        operation: StorageOperation,
+        result: database::Result<()>,
+    ) -> bool {
+        match result {
+            Ok(()) => {
+                if let Some(storage_error) = &self.storage_error {
+                    if storage_error.operation == operation {
+                        self.storage_error = None;
+                    }
+                }
+                true
+            }
+            Err(error) => {
+                self.storage_error = Some(StorageError { operation, error });
+                false
+            }
+        }
+    }
+}
+
+impl Application for SimpleTimeTracker {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Flags = DatabaseLocation;
+
+    fn new(flags: DatabaseLocation) -> (Self, Command<Message>) {
+        let mut simple_time_tracker = Self {
+            storage: SqliteStorage::new(flags.path),
+            adopt_legacy_database: flags.adopt_legacy,
+            is_dark_mode: true,
+            tracker: Tracker::empty(Rc::new(SystemClock)),
+            selected_timer: 0,
+            pomodoro: Pomodoro::new(PomodoroSettings::default(), &SystemClock),
+            is_pomodoro_mode: false,
+            history: History::default(),
+            toast: None,
+            alerted_time_boxes: Vec::new(),
+            tracked_time_rows: Vec::new(),
+            project_delete_buttons: Vec::new(),
+            selected_project: None,
+            tag_filter: Vec::new(),
+            is_loaded: false,
+            storage_error: None,
+            deleted_tracked_times: Vec::new(),
+            deleted_timers: Vec::new(),
+
+            should_exit: false,
+            close_requested: false,
+            start_stop_button: button::State::new(),
+            clear_button: button::State::new(),
+            timer_buttons: Vec::new(),
+            timer_name_text_input: text_input::State::new(),
+            timer_name_input: String::new(),
+            add_timer_button: button::State::new(),
+            remove_timer_button: button::State::new(),
+            time_box_text_input: text_input::State::new(),
+            time_box_input: String::new(),
+            pomodoro_button: button::State::new(),
+            pomodoro_work_text_input: text_input::State::new(),
+            pomodoro_work_input: String::new(),
+            pomodoro_short_break_text_input: text_input::State::new(),
+            pomodoro_short_break_input: String::new(),
+            pomodoro_long_break_text_input: text_input::State::new(),
+            pomodoro_long_break_input: String::new(),
+            pomodoro_cycles_text_input: text_input::State::new(),
+            pomodoro_cycles_input: String::new(),
+            dark_mode_button: button::State::new(),
+            time_text_input: text_input::State::new(),
+            time_input: String::new(),
+            description_text_input: text_input::State::new(),
+            description_input: String::new(),
+            index_text_input: text_input::State::new(),
+            index_input: String::new(),
+            index_suggestions_pick_list: pick_list::State::default(),
+            target_index_text_input: text_input::State::new(),
+            target_index_input: String::new(),
+            target_index_suggestions_pick_list: pick_list::State::default(),
+            operation: Operation::Add,
+            operation_pick_list: pick_list::State::default(),
+            operation_error: None,
+            apply_operation_button: button::State::new(),
+            tracked_times_scroll: scrollable::State::new(),
+            retry_button: button::State::new(),
+            dismiss_error_button: button::State::new(),
+            project_pick_list: pick_list::State::default(),
+            show_projects: false,
+            projects_button: button::State::new(),
+            project_name_text_input: text_input::State::new(),
+            project_name_input: String::new(),
+            client_name_text_input: text_input::State::new(),
+            client_name_input: String::new(),
+            add_project_button: button::State::new(),
+            show_export: false,
+            export_button: button::State::new(),
+            export_format: ExportFormat::Csv,
+            export_format_pick_list: pick_list::State::default(),
+            export_path_text_input: text_input::State::new(),
+            export_path_input: default_export_path("csv"),
+            export_from_text_input: text_input::State::new(),
+            export_from_input: String::new(),
+            export_to_text_input: text_input::State::new(),
+            export_to_input: String::new(),
+            export_columns: csv::DEFAULT_COLUMNS.to_vec(),
+            export_file_button: button::State::new(),
+            import_path_text_input: text_input::State::new(),
+            import_path_input: String::new(),
+            import_merge_button: button::State::new(),
+            import_replace_button: button::State::new(),
+            import_format: ImportFormat::Json,
+            import_format_pick_list: pick_list::State::default(),
+            import_preview_button: button::State::new(),
+            import_preview: None,
+            tag_filter_buttons: Vec::new(),
+            clear_tag_filter_button: button::State::new(),
+            undo_toast_button: button::State::new(),
+        };
+        simple_time_tracker.set_pomodoro_settings(PomodoroSettings::default());
+        simple_time_tracker.load();
+
+        (simple_time_tracker, Command::none())
+    }
+
+    fn title(&self) -> String {
+        String::from("Simple Time Tracker")
+    }
+
+    fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::EventOccurred(event) => match event {
+                Event::Window(iced_native::window::Event::CloseRequested) => {
+                    // A second close request exits even if the changes could not be stored
+                    if self.save_unsaved() || self.close_requested {
+                        self.should_exit = true;
+                    }
+                    self.close_requested = true;
+                }
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key_code: keyboard::KeyCode::Z,
+                    modifiers,
+                }) if modifiers.is_command_pressed() && !self.is_text_input_focused() => {
+                    if modifiers.shift {
+                        self.redo();
+                    } else {
+                        self.undo();
+                    }
+                }
+                _ => {}
+            },
+            Message::TimeUpdate => {
+                self.log_pomodoro_sessions();
+                self.alert_elapsed_time_boxes();
+                if let Some(toast) = &self.toast {
+                    if toast.shown_at.elapsed() >= TOAST_DURATION {
+                        self.toast = None;
+                    }
+                }
+            }
+            Message::StartStopTimer if self.is_pomodoro_mode => {
+                self.pomodoro.start_stop(self.tracker.clock.as_ref());
+            }
+            Message::StartStopTimer => {
+                self.history.record(&self.tracker);
+                self.tracker.start_stop(self.selected_timer);
+                self.save_timer(self.selected_timer);
+            }
+            Message::ClearTimer if self.is_pomodoro_mode => {
+                self.pomodoro.reset(self.tracker.clock.as_ref());
+            }
+            Message::ClearTimer => {
+                self.history.record(&self.tracker);
+                self.tracker.clear(self.selected_timer);
+                self.save_timer(self.selected_timer);
+                let name = &self.tracker.timers[self.selected_timer].name;
+                self.show_toast(format!("{} cleared", name));
+            }
+            Message::SelectTimer(i) => {
+                self.selected_timer = i;
+                self.operation_error = None;
+            }
+            Message::TimerNameInputChanged(input) => self.timer_name_input = input,
+            Message::TogglePomodoro => self.is_pomodoro_mode = !self.is_pomodoro_mode,
+            Message::PomodoroSettingChanged(setting, input) => {
+                self.change_pomodoro_setting(setting, input)
+            }
+            Message::AddTimer => {
+                self.history.record(&self.tracker);
+                match self.tracker.add_timer(&self.timer_name_input) {
+                    Ok(i) => {
+                        self.selected_timer = i;
+                        self.timer_name_input.clear();
+                        self.update_rows();
+                        self.save_timer(i);
+                    }
+                    Err(_) => self.history.discard(),
+                }
+            }
+            Message::RemoveTimer => {
+                self.history.record(&self.tracker);
+                match self.tracker.remove_timer(self.selected_timer) {
+                    Ok(timer) => {
+                        self.update_rows();
+                        self.deleted_timers.extend(timer.id);
+                        self.save_changes(&[], &[]);
+                        self.show_toast(format!("Removed {}", timer.name));
+                    }
+                    Err(_) => self.history.discard(),
+                }
+            }
+            Message::TimeBoxInputChanged(input) => self.time_box_input = input,
+            Message::SetTimeBox => self.set_time_box(),
+            Message::DarkModeToggle => {
+                self.is_dark_mode = !self.is_dark_mode;
+                if self.is_loaded {
+                    let result = self
+                        .storage
+                        .store_setting(DARKMODE_KEY, self.is_dark_mode as i64);
+                    self.handle_storage_result(StorageOperation::StoreSettings, result);
+                }
+            }
+            Message::TimeInputChanged(input) => {
+                self.time_input = input;
+                self.operation_error = None;
+            }
+            Message::DescriptionInputChanged(input) => {
+                self.description_input = input;
+                self.operation_error = None;
+            }
+            Message::IndexInputChanged(input) => {
+                self.index_input = input;
+                self.operation_error = None;
+            }
+            Message::TargetIndexInputChanged(input) => {
+                self.target_index_input = input;
+                self.operation_error = None;
+            }
+            Message::IndexSuggestionSelected(suggestion) => {
+                self.index_input = suggestion.number.to_string();
+                self.operation_error = None;
+            }
+            Message::TargetIndexSuggestionSelected(suggestion) => {
+                self.target_index_input = suggestion.number.to_string();
+                self.operation_error = None;
+            }
+            Message::OperationSelected(operation) => {
+                self.operation = operation;
+                self.operation_error = None;
+            }
+            Message::ApplyOperation => {
+                self.history.record(&self.tracker);
+                match self.apply_operation() {
+                    Ok(changed) => {
+                        self.operation_error = None;
+                        if self.operation == Operation::Subtract {
+                            self.show_toast(format!(
+                                "Time subtracted from entry {}",
+                                changed[0] + 1
+                            ));
+                        }
+                        // Only adding and returning change the timer
+                        let timers = match self.operation {
+                            Operation::Add | Operation::Return => vec![self.selected_timer],
+                            Operation::Subtract | Operation::Move => Vec::new(),
+                        };
+                        self.save_changes(&changed, &timers);
+                    }
+                    Err(error) => {
+                        self.history.discard();
+                        self.operation_error = Some(error);
+                    }
+                }
+            }
+            Message::DeleteTrackedTime(i) => {
+                self.history.record(&self.tracker);
+                let message = format!("Deleted \"{}\"", self.tracker.tracked_times[i].description);
+                self.delete_tracked_time(i);
+                let tags = self.tracker.tags();
+                self.tag_filter.retain(|tag| tags.contains(tag));
+                self.show_toast(message);
+            }
+            Message::CopyText(i) => {
+                clipboard.write(self.tracker.tracked_times[i].description.clone());
+            }
+            Message::ToggleIntervals(i) => {
+                let row = &mut self.tracked_time_rows[i];
+                row.is_expanded = !row.is_expanded;
+            }
+            Message::EditTrackedTime(i) => {
+                let tracked_time = &self.tracker.tracked_times[i];
+                let row = &mut self.tracked_time_rows[i];
+                // Tags are edited as part of the description like when the entry was added
+                row.description_input = std::iter::once(tracked_time.description.clone())
+                    .chain(tracked_time.tags.iter().map(|tag| format!("#{}", tag)))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                let duration = tracked_time.duration();
+                row.duration_input = format!(
+                    "{}:{:02}:{:02}",
+                    duration.num_hours(),
+                    duration.num_minutes() % 60,
+                    duration.num_seconds() % 60
+                );
+                row.edit_error = None;
+                row.is_editing = true;
+            }
+            Message::EditDescriptionChanged(i, input) => {
+                let row = &mut self.tracked_time_rows[i];
+                row.description_input = input;
+                row.edit_error = None;
+            }
+            Message::EditDurationChanged(i, input) => {
+                let row = &mut self.tracked_time_rows[i];
+                row.duration_input = input;
+                row.edit_error = None;
+            }
+            Message::SaveEdit(i) => {
+                self.history.record(&self.tracker);
+                let row = &self.tracked_time_rows[i];
+                let result = self
+                    .tracker
+                    .edit(i + 1, &row.description_input, &row.duration_input);
+                match result {
+                    Ok(_) => {
+                        let row = &mut self.tracked_time_rows[i];
+                        row.is_editing = false;
+                        row.intervals.clear();
+                        self.update_rows();
+                        self.save_tracked_time(i);
+                        let tags = self.tracker.tags();
+                        self.tag_filter.retain(|tag| tags.contains(tag));
+                    }
+                    Err(error) => {
+                        self.history.discard();
+                        self.tracked_time_rows[i].edit_error = Some(error.to_string());
+                    }
+                }
+            }
+            Message::CancelEdit(i) => self.tracked_time_rows[i].is_editing = false,
+            Message::AddInterval(i) => {
+                self.history.record(&self.tracker);
+                self.tracker.add_interval(i);
+                self.update_rows();
+                self.save_tracked_time(i);
+            }
+            Message::IntervalStartChanged(i, j, input) => {
+                self.tracked_time_rows[i].intervals[j].start_input = input;
+            }
+            Message::IntervalEndChanged(i, j, input) => {
+                self.tracked_time_rows[i].intervals[j].end_input = input;
+            }
+            Message::SaveInterval(i, j) => {
+                self.history.record(&self.tracker);
+                let row = &mut self.tracked_time_rows[i].intervals[j];
+                let is_changed = match (
+                    model::parse_date_time(&row.start_input),
+                    model::parse_date_time(&row.end_input),
+                ) {
+                    (Some(start), Some(end)) => self.tracker.set_interval(i, j, start, end).is_ok(),
+                    _ => false,
+                };
+                row.reset_inputs(&self.tracker.tracked_times[i].intervals[j]);
+                if is_changed {
+                    self.save_tracked_time(i);
+                } else {
+                    self.history.discard();
+                }
+            }
+            Message::DeleteInterval(i, j) => {
+                self.history.record(&self.tracker);
+                self.tracker.delete_interval(i, j);
+                self.tracked_time_rows[i].intervals.remove(j);
+                self.save_tracked_time(i);
+            }
+            Message::RetryStorage => match self.storage_error.as_ref().map(|error| error.operation)
+            {
+                Some(StorageOperation::Load) => self.load(),
+                Some(StorageOperation::StoreProjects) => {
+                    let result = self.load_projects();
+                    self.handle_storage_result(StorageOperation::StoreProjects, result);
+                }
+                _ => {
+                    if self.save_unsaved() && self.close_requested {
+                        self.should_exit = true;
+                    }
+                }
+            },
+            Message::DismissStorageError => self.storage_error = None,
+            Message::ProjectSelected(option) => self.selected_project = option.id,
+            Message::ToggleProjects => self.show_projects = !self.show_projects,
+            Message::ToggleExport => self.show_export = !self.show_export,
+            Message::ExportFormatSelected(format) => {
+                self.export_format = format;
+                self.export_path_input = PathBuf::from(self.export_path_input.trim())
+                    .with_extension(format.extension())
+                    .display()
+                    .to_string();
+            }
+            Message::ExportPathInputChanged(input) => self.export_path_input = input,
+            Message::ExportFromInputChanged(input) => self.export_from_input = input,
+            Message::ExportToInputChanged(input) => self.export_to_input = input,
+            Message::ExportColumnToggled(column, is_checked) => {
+                self.export_columns.retain(|c| *c != column);
+                if is_checked {
+                    self.export_columns.push(column);
+                }
+            }
+            Message::Export => self.export(),
+            Message::ImportPathInputChanged(input) => {
+                self.import_path_input = input;
+                self.import_preview = None;
+            }
+            Message::ImportFormatSelected(format) => {
+                self.import_format = format;
+                self.import_preview = None;
+            }
+            Message::PreviewImport => self.preview_import(),
+            Message::Import(mode) => self.import(mode),
+            Message::ProjectNameInputChanged(input) => self.project_name_input = input,
+            Message::ClientNameInputChanged(input) => self.client_name_input = input,
+            Message::AddProject => {
+                let name = self.project_name_input.trim().to_string();
+                if name.is_empty() || !self.is_loaded {
+                    return Command::none();
+                }
+                let color = PROJECT_COLORS[self.tracker.projects.len() % PROJECT_COLORS.len()];
+                let result = self
+                    .storage
+                    .insert_project(&name, self.client_name_input.trim(), color)
+                    .and_then(|id| self.load_projects().map(|_| id));
+                if let Ok(id) = result {
+                    self.selected_project = Some(id);
+                    self.project_name_input.clear();
+                    self.client_name_input.clear();
+                }
+                self.handle_storage_result(StorageOperation::StoreProjects, result.map(|_| ()));
+            }
+            Message::DeleteProject(i) => {
+                if !self.is_loaded {
+                    return Command::none();
+                }
+                let id = self.tracker.projects[i].id;
+                let result = self
+                    .storage
+                    .delete_project(id)
+                    .and_then(|_| self.load_projects());
+                if result.is_ok() {
+                    self.tracker.unassign_project(id);
+                    // Earlier states could still refer to the deleted project
+                    self.history.clear();
+                }
+                self.handle_storage_result(StorageOperation::StoreProjects, result);
+            }
+            Message::ToggleTagFilter(tag) => match self.tag_filter.iter().position(|t| *t == tag) {
+                Some(i) => {
+                    self.tag_filter.remove(i);
+                }
+                None => self.tag_filter.push(tag),
+            },
+            Message::ClearTagFilter => self.tag_filter.clear(),
+            Message::Undo => self.undo(),
+        }
+
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = Vec::new();
+        subscriptions.push(iced_native::subscription::events().map(Message::EventOccurred));
+        if self.tracker.is_any_timer_running() || self.pomodoro.is_running() || self.toast.is_some()
+        {
+            subscriptions.push(
+                time::every(std::time::Duration::from_millis(500)).map(|_| Message::TimeUpdate),
+            );
+        }
+        return Subscription::batch(subscriptions);
+    }
+
+    fn should_exit(&self) -> bool {
+        self.should_exit
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        let tags = self.tracker.tags();
+        let project_options = self.project_options();
+        let selected_project = self.selected_project;
+        let selected_project_option = project_options
+            .iter()
+            .find(|option| option.id == selected_project)
+            .cloned();
+
+        // The pomodoro counts down the current phase and a timer with a time box counts down the box
+        let clock = self.tracker.clock.as_ref();
+        let selected_timer = &self.tracker.timers[self.selected_timer];
+        let (remaining, is_running, is_overrun) = match self.is_pomodoro_mode {
+            true => (
+                self.pomodoro.remaining(clock),
+                self.pomodoro.is_running(),
+                false,
+            ),
+            false => (
+                selected_timer
+                    .remaining(clock)
+                    .unwrap_or_else(|| self.tracker.current_duration(self.selected_timer)),
+                selected_timer.is_running,
+                selected_timer.is_overrun(clock),
+            ),
+        };
+        let (hours_minutes, seconds) = format_time_box(remaining);
+        let has_invalid_pomodoro_settings = self.has_invalid_pomodoro_settings();
+        let return_label = match self.tracker.timers.len() {
+            1 => String::from("back to the timer"),
+            _ => format!("back to {}", self.tracker.timers[self.selected_timer].name),
+        };
+        let index_suggestions = self.entry_suggestions(&self.index_input);
+        let target_index_suggestions = self.entry_suggestions(&self.target_index_input);
+
+        // Failed operations are reported until the inputs change, the time input is checked live
+        let available_duration = self.available_duration();
+        let operation_error = match (&self.operation_error, available_duration) {
+            (Some(error), _) => Some(error.clone()),
+            (None, Some(available)) if !self.time_input.is_empty() => {
+                timer::parse_duration(&self.time_input, available)
+                    .err()
+                    .map(|error| error.to_string())
+            }
+            _ => None,
+        };
+        let operation_message: Element<Message> = match operation_error {
+            Some(error) => Container::new(Text::new(error).size(14))
+                .style(style::ValidationStyle)
+                .into(),
+            None => Space::with_height(Length::Shrink).into(),
+        };
+
+        let time = Row::new()
+            .push(
+                Container::new(Text::new(hours_minutes).size(60)).style(style::TimerStyle {
                     is_dark_mode: self.is_dark_mode,
-                    is_running: self.is_running,
+                    is_running,
+                    is_overrun,
                     opacity: 1.0,
                 }),
             )
             .push(Container::new(
-                Container::new(Text::new(format!(":{:02}", duration.num_seconds() % 60)).size(60))
-                    .style(style::TimerStyle {
-                        is_dark_mode: self.is_dark_mode,
-                        is_running: self.is_running,
-                        opacity: 0.5,
-                    }),
+                Container::new(Text::new(seconds).size(60)).style(style::TimerStyle {
+                    is_dark_mode: self.is_dark_mode,
+                    is_running,
+                    is_overrun,
+                    opacity: 0.5,
+                }),
             ));
 
         let start_stop_button = Container::new(
             Button::new(
                 &mut self.start_stop_button,
-                Container::new(match self.is_running {
+                Container::new(match is_running {
                     true => Text::new("Pause"),
                     false => Text::new("Start"),
                 })
@@ -356,6 +1844,25 @@ impl Application for SimpleTimeTracker {
         .height(Length::Units(60))
         .center_y();
 
+        let pomodoro_button = Container::new(
+            Button::new(
+                &mut self.pomodoro_button,
+                Container::new(match self.is_pomodoro_mode {
+                    true => Text::new("Timers"),
+                    false => Text::new("Pomodoro"),
+                })
+                .center_x()
+                .width(Length::Units(75)),
+            )
+            .on_press(Message::TogglePomodoro)
+            .style(style::ButtonStyle {
+                is_dark_mode: self.is_dark_mode,
+                foreground: None,
+            }),
+        )
+        .height(Length::Units(60))
+        .center_y();
+
         let dark_mode_button = Container::new(
             Button::new(
                 &mut self.dark_mode_button,
@@ -375,92 +1882,930 @@ impl Application for SimpleTimeTracker {
         .height(Length::Units(60))
         .center_y();
 
-        let timer_operations = Container::new(
-            Row::new()
-                .push(
-                    Row::new()
-                        .push(Space::with_width(Length::Fill))
-                        .push(
-                            Container::new(Text::new("Add "))
-                                .height(Length::Fill)
-                                .center_y()
-                                .style(style::TextStyle {
-                                    is_dark_mode: self.is_dark_mode,
-                                }),
-                        )
-                        .push(
-                            Container::new(
-                                TextInput::new(
-                                    &mut self.time_text_input,
-                                    "all",
-                                    &self.time_input,
-                                    Message::TimeInputChanged,
-                                )
-                                .on_submit(Message::ApplyOperation)
-                                .padding(3)
-                                .width(Length::Units(50))
-                                .style(style::TextInputStyle {
+        let projects_button = Container::new(
+            Button::new(
+                &mut self.projects_button,
+                Container::new(Text::new("Projects"))
+                    .center_x()
+                    .width(Length::Units(75)),
+            )
+            .on_press(Message::ToggleProjects)
+            .style(style::ButtonStyle {
+                is_dark_mode: self.is_dark_mode,
+                foreground: None,
+            }),
+        )
+        .height(Length::Units(60))
+        .center_y();
+
+        let export_button = Container::new(
+            Button::new(
+                &mut self.export_button,
+                Container::new(Text::new("Files"))
+                    .center_x()
+                    .width(Length::Units(75)),
+            )
+            .on_press(Message::ToggleExport)
+            .style(style::ButtonStyle {
+                is_dark_mode: self.is_dark_mode,
+                foreground: None,
+            }),
+        )
+        .height(Length::Units(60))
+        .center_y();
+
+        let mut timers_bar = Row::new().spacing(4);
+        let tracker = &self.tracker;
+        for (i, (timer, timer_button)) in tracker
+            .timers
+            .iter()
+            .zip(self.timer_buttons.iter_mut())
+            .enumerate()
+        {
+            let timer_duration = tracker.current_duration(i);
+            let time_box = timer
+                .time_box
+                .map(|time_box| format!(" / {}", model::format_duration(time_box)))
+                .unwrap_or_default();
+            timers_bar = timers_bar.push(
+                Container::new(
+                    Button::new(
+                        timer_button,
+                        Row::new()
+                            .push(Text::new(&timer.name))
+                            .push(Space::with_width(Length::Units(6)))
+                            .push(
+                                Container::new(Text::new(format!(
+                                    "{}:{:02}:{:02}{}",
+                                    timer_duration.num_hours(),
+                                    timer_duration.num_minutes() % 60,
+                                    timer_duration.num_seconds() % 60,
+                                    time_box
+                                )))
+                                .style(style::TimerStyle {
                                     is_dark_mode: self.is_dark_mode,
+                                    is_running: timer.is_running,
+                                    is_overrun: timer.is_overrun(tracker.clock.as_ref()),
+                                    opacity: 1.0,
                                 }),
-                            )
-                            .height(Length::Fill)
-                            .center_y(),
-                        )
-                        .push(Space::with_width(Length::Units(16)))
-                        .width(Length::FillPortion(1)),
+                            ),
+                    )
+                    .on_press(Message::SelectTimer(i))
+                    .padding(3)
+                    .style(style::ButtonStyle {
+                        is_dark_mode: self.is_dark_mode,
+                        foreground: if i == self.selected_timer {
+                            Some(style::PRIMARY)
+                        } else {
+                            None
+                        },
+                    }),
                 )
-                .push(
-                    Column::new()
-                        .push(
-                            Row::new()
-                                .push(
-                                    Container::new(Text::new("to new entry called "))
-                                        .height(Length::Fill)
-                                        .center_y()
-                                        .style(style::TextStyle {
-                                            is_dark_mode: self.is_dark_mode,
-                                        }),
-                                )
-                                .push(
-                                    Container::new(
-                                        TextInput::new(
-                                            &mut self.description_text_input,
-                                            "description",
-                                            &self.description_input,
-                                            Message::DescriptionInputChanged,
-                                        )
-                                        .on_submit(Message::ApplyOperation)
-                                        .padding(3)
-                                        .style(
-                                            style::TextInputStyle {
-                                                is_dark_mode: self.is_dark_mode,
-                                            },
-                                        ),
-                                    )
-                                    .height(Length::Fill)
-                                    .width(Length::Fill)
-                                    .center_y(),
+                .height(Length::Fill)
+                .center_y(),
+            );
+        }
+        timers_bar = timers_bar
+            .push(Space::with_width(Length::Fill))
+            .push(
+                Container::new(
+                    TextInput::new(
+                        &mut self.time_box_text_input,
+                        "box: 45m for ...",
+                        &self.time_box_input,
+                        Message::TimeBoxInputChanged,
+                    )
+                    .on_submit(Message::SetTimeBox)
+                    .padding(3)
+                    .width(Length::Units(130))
+                    .style(style::TextInputStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                )
+                .height(Length::Fill)
+                .center_y(),
+            )
+            .push(Space::with_width(Length::Units(8)))
+            .push(
+                Container::new(
+                    TextInput::new(
+                        &mut self.timer_name_text_input,
+                        "timer name",
+                        &self.timer_name_input,
+                        Message::TimerNameInputChanged,
+                    )
+                    .on_submit(Message::AddTimer)
+                    .padding(3)
+                    .width(Length::Units(110))
+                    .style(style::TextInputStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                )
+                .height(Length::Fill)
+                .center_y(),
+            )
+            .push(
+                Container::new(
+                    Button::new(
+                        &mut self.add_timer_button,
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(Text::new("Add Timer"))
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .on_press(Message::AddTimer)
+                    .padding(3)
+                    .style(style::ButtonStyle {
+                        is_dark_mode: self.is_dark_mode,
+                        foreground: None,
+                    }),
+                )
+                .height(Length::Fill)
+                .center_y(),
+            );
+        if self.tracker.timers.len() > 1 {
+            timers_bar = timers_bar.push(
+                Container::new(
+                    Button::new(
+                        &mut self.remove_timer_button,
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(Text::new("Remove"))
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .on_press(Message::RemoveTimer)
+                    .padding(3)
+                    .style(style::ButtonStyle {
+                        is_dark_mode: self.is_dark_mode,
+                        foreground: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
+                    }),
+                )
+                .height(Length::Fill)
+                .center_y(),
+            );
+        }
+        let timers_bar = Container::new(timers_bar)
+            .height(Length::Units(30))
+            .width(Length::Fill);
+
+        let pomodoro_phase = match self.pomodoro.phase {
+            Phase::Work => format!(
+                "{} {}/{}",
+                self.pomodoro.phase,
+                self.pomodoro.cycle(),
+                self.pomodoro.settings.cycles
+            ),
+            phase => phase.to_string(),
+        };
+        let pomodoro_message: Element<Message> = match has_invalid_pomodoro_settings {
+            true => Container::new(Text::new("Lengths and cycles must be above zero").size(14))
+                .height(Length::Fill)
+                .center_y()
+                .style(style::ValidationStyle)
+                .into(),
+            false => Space::with_width(Length::Shrink).into(),
+        };
+        let mut pomodoro_bar = Row::new()
+            .spacing(4)
+            .push(
+                Container::new(Text::new(pomodoro_phase))
+                    .height(Length::Fill)
+                    .center_y()
+                    .style(style::TextStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+            )
+            .push(Space::with_width(Length::Units(8)))
+            .push(pomodoro_message)
+            .push(Space::with_width(Length::Fill));
+        for (label, setting, state, input) in [
+            (
+                "Work",
+                PomodoroSetting::Work,
+                &mut self.pomodoro_work_text_input,
+                &self.pomodoro_work_input,
+            ),
+            (
+                "Short break",
+                PomodoroSetting::ShortBreak,
+                &mut self.pomodoro_short_break_text_input,
+                &self.pomodoro_short_break_input,
+            ),
+            (
+                "Long break",
+                PomodoroSetting::LongBreak,
+                &mut self.pomodoro_long_break_text_input,
+                &self.pomodoro_long_break_input,
+            ),
+            (
+                "Cycles",
+                PomodoroSetting::Cycles,
+                &mut self.pomodoro_cycles_text_input,
+                &self.pomodoro_cycles_input,
+            ),
+        ] {
+            pomodoro_bar = pomodoro_bar
+                .push(
+                    Container::new(Text::new(label).size(16))
+                        .height(Length::Fill)
+                        .center_y()
+                        .style(style::TextStyle {
+                            is_dark_mode: self.is_dark_mode,
+                        }),
+                )
+                .push(
+                    Container::new(
+                        TextInput::new(state, "", input, move |input| {
+                            Message::PomodoroSettingChanged(setting, input)
+                        })
+                        .padding(3)
+                        .size(16)
+                        .width(Length::Units(40))
+                        .style(style::TextInputStyle {
+                            is_dark_mode: self.is_dark_mode,
+                        }),
+                    )
+                    .height(Length::Fill)
+                    .center_y(),
+                )
+                .push(Space::with_width(Length::Units(4)));
+        }
+        let pomodoro_bar = Container::new(pomodoro_bar)
+            .height(Length::Units(30))
+            .width(Length::Fill);
+
+        let operation_inputs: Element<Message> = match self.operation {
+            Operation::Add => Column::new()
+                .push(
+                    Row::new()
+                        .push(
+                            Container::new(Text::new("to new entry called "))
+                                .height(Length::Fill)
+                                .center_y()
+                                .style(style::TextStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                        )
+                        .push(
+                            Container::new(
+                                TextInput::new(
+                                    &mut self.description_text_input,
+                                    "description",
+                                    &self.description_input,
+                                    Message::DescriptionInputChanged,
                                 )
-                                .height(Length::FillPortion(3)),
+                                .on_submit(Message::ApplyOperation)
+                                .padding(3)
+                                .style(style::TextInputStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .width(Length::Fill)
+                            .center_y(),
                         )
+                        .push(Space::with_width(Length::Units(4)))
                         .push(
-                            Row::new()
-                                .push(Rule::horizontal(8).style(style::RuleStyle))
-                                .push(
-                                    Container::new(Text::new("or"))
-                                        .height(Length::Fill)
-                                        .center_y()
-                                        .style(style::TextStyle {
-                                            is_dark_mode: self.is_dark_mode,
-                                        }),
+                            Container::new(
+                                PickList::new(
+                                    &mut self.project_pick_list,
+                                    project_options,
+                                    selected_project_option,
+                                    Message::ProjectSelected,
+                                )
+                                .padding(3)
+                                .width(Length::Units(110))
+                                .style(style::PickListStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .height(Length::FillPortion(3)),
+                )
+                .push(
+                    Row::new()
+                        .push(Rule::horizontal(8).style(style::RuleStyle))
+                        .push(
+                            Container::new(Text::new("or"))
+                                .height(Length::Fill)
+                                .center_y()
+                                .style(style::TextStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                        )
+                        .push(Rule::horizontal(8).style(style::RuleStyle))
+                        .height(Length::FillPortion(2)),
+                )
+                .push(
+                    Row::new()
+                        .push(
+                            Container::new(Text::new("existing entry "))
+                                .height(Length::Fill)
+                                .center_y()
+                                .style(style::TextStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                        )
+                        .push(
+                            Container::new(
+                                TextInput::new(
+                                    &mut self.index_text_input,
+                                    "#, @ID or text",
+                                    &self.index_input,
+                                    Message::IndexInputChanged,
+                                )
+                                .on_submit(Message::ApplyOperation)
+                                .padding(3)
+                                .width(Length::Units(80))
+                                .style(style::TextInputStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(suggestion_pick_list(
+                            &mut self.index_suggestions_pick_list,
+                            index_suggestions,
+                            Message::IndexSuggestionSelected,
+                            self.is_dark_mode,
+                        ))
+                        .height(Length::FillPortion(3)),
+                )
+                .width(Length::FillPortion(2))
+                .into(),
+            _ => {
+                let source_row = Row::new()
+                    .push(
+                        Container::new(Text::new("from entry "))
+                            .height(Length::Fill)
+                            .center_y()
+                            .style(style::TextStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                    )
+                    .push(
+                        Container::new(
+                            TextInput::new(
+                                &mut self.index_text_input,
+                                "#, @ID or text",
+                                &self.index_input,
+                                Message::IndexInputChanged,
+                            )
+                            .on_submit(Message::ApplyOperation)
+                            .padding(3)
+                            .width(Length::Units(80))
+                            .style(style::TextInputStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                        )
+                        .height(Length::Fill)
+                        .center_y(),
+                    )
+                    .push(suggestion_pick_list(
+                        &mut self.index_suggestions_pick_list,
+                        index_suggestions,
+                        Message::IndexSuggestionSelected,
+                        self.is_dark_mode,
+                    ))
+                    .height(Length::FillPortion(3));
+                let target_row = match self.operation {
+                    Operation::Move => Row::new()
+                        .push(
+                            Container::new(Text::new("to entry "))
+                                .height(Length::Fill)
+                                .center_y()
+                                .style(style::TextStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                        )
+                        .push(
+                            Container::new(
+                                TextInput::new(
+                                    &mut self.target_index_text_input,
+                                    "#, @ID or text",
+                                    &self.target_index_input,
+                                    Message::TargetIndexInputChanged,
+                                )
+                                .on_submit(Message::ApplyOperation)
+                                .padding(3)
+                                .width(Length::Units(80))
+                                .style(style::TextInputStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(suggestion_pick_list(
+                            &mut self.target_index_suggestions_pick_list,
+                            target_index_suggestions,
+                            Message::TargetIndexSuggestionSelected,
+                            self.is_dark_mode,
+                        )),
+                    Operation::Return => Row::new().push(
+                        Container::new(Text::new(return_label))
+                            .height(Length::Fill)
+                            .center_y()
+                            .style(style::TextStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                    ),
+                    _ => Row::new().push(
+                        Container::new(Text::new("and discard it"))
+                            .height(Length::Fill)
+                            .center_y()
+                            .style(style::TextStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                    ),
+                };
+                Column::new()
+                    .push(Space::with_height(Length::FillPortion(1)))
+                    .push(source_row)
+                    .push(target_row.height(Length::FillPortion(3)))
+                    .push(Space::with_height(Length::FillPortion(1)))
+                    .width(Length::FillPortion(2))
+                    .into()
+            }
+        };
+
+        let timer_operations = Container::new(
+            Row::new()
+                .push(
+                    Row::new()
+                        .push(Space::with_width(Length::Fill))
+                        .push(
+                            Container::new(
+                                PickList::new(
+                                    &mut self.operation_pick_list,
+                                    &Operation::ALL[..],
+                                    Some(self.operation),
+                                    Message::OperationSelected,
+                                )
+                                .padding(3)
+                                .width(Length::Units(90))
+                                .style(style::PickListStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Units(4)))
+                        .push(
+                            Container::new(
+                                TextInput::new(
+                                    &mut self.time_text_input,
+                                    "all",
+                                    &self.time_input,
+                                    Message::TimeInputChanged,
+                                )
+                                .on_submit(Message::ApplyOperation)
+                                .padding(3)
+                                .width(Length::Units(70))
+                                .style(style::TextInputStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Units(16)))
+                        .width(Length::FillPortion(1)),
+                )
+                .push(operation_inputs)
+                .push(
+                    Row::new()
+                        .push(Space::with_width(Length::Units(24)))
+                        .push(
+                            Container::new(
+                                Column::new()
+                                    .spacing(4)
+                                    .push(
+                                        Button::new(
+                                            &mut self.apply_operation_button,
+                                            Row::new()
+                                                .push(Space::with_width(Length::Units(12)))
+                                                .push(Text::new("Apply"))
+                                                .push(Space::with_width(Length::Units(12))),
+                                        )
+                                        .on_press(Message::ApplyOperation)
+                                        .padding(3)
+                                        .style(
+                                            style::ButtonStyle {
+                                                is_dark_mode: self.is_dark_mode,
+                                                foreground: None,
+                                            },
+                                        ),
+                                    )
+                                    .push(operation_message),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Fill))
+                        .width(Length::FillPortion(1)),
+                )
+                .height(Length::Units(80)),
+        )
+        .padding(8)
+        .width(Length::Fill)
+        .center_x();
+
+        let mut tracked_times_list = Column::new().spacing(6);
+        let mut total = chrono::Duration::zero();
+
+        let tag_filter = &self.tag_filter;
+        let tracker = &self.tracker;
+        for (i, (tracked_time, row)) in tracker
+            .tracked_times
+            .iter()
+            .zip(self.tracked_time_rows.iter_mut())
+            .enumerate()
+        {
+            if !tracked_time.has_tags(tag_filter) {
+                continue;
+            }
+            let duration = tracked_time.duration();
+            total = total + duration;
+            let project = tracked_time
+                .project_id
+                .and_then(|id| tracker.project(id))
+                .map(|project| (project.name.clone(), style::project_color(project.color)));
+            let project_label: Element<Message> = match project {
+                Some((name, color)) => Container::new(
+                    Container::new(Text::new(name).size(16))
+                        .padding(3)
+                        .style(style::ProjectLabelStyle { color }),
+                )
+                .height(Length::Fill)
+                .center_y()
+                .into(),
+                None => Space::with_width(Length::Shrink).into(),
+            };
+            // Entries taken off a time box show how much of it was left or how far it was overrun
+            let planned_label: Element<Message> =
+                match (tracked_time.planned, tracked_time.remaining()) {
+                    (Some(planned), Some(remaining)) => Container::new(
+                        Row::new().push(Space::with_width(Length::Units(6))).push(
+                            match remaining < chrono::Duration::zero() {
+                                true => {
+                                    Text::new(format!("+{}", model::format_duration(-remaining)))
+                                        .size(16)
+                                        .color(style::OVERRUN)
+                                }
+                                false => {
+                                    Text::new(format!("of {}", model::format_duration(planned)))
+                                        .size(16)
+                                        .color(style::GRAY)
+                                }
+                            },
+                        ),
+                    )
+                    .height(Length::Fill)
+                    .center_y()
+                    .into(),
+                    _ => Space::with_width(Length::Shrink).into(),
+                };
+            let mut entry = Column::new().spacing(2).push(
+                Container::new(
+                    Row::new()
+                        .push(
+                            Container::new(
+                                Row::new()
+                                    .push(Space::with_width(Length::Units(4)))
+                                    .push(Text::new(format!("{}", i + 1)).size(28))
+                                    .push(Space::with_width(Length::Units(4))),
+                            )
+                            .height(Length::Fill)
+                            .width(Length::Units(50))
+                            .center_x()
+                            .center_y()
+                            .style(style::IndexStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                        )
+                        .push(Space::with_width(Length::Units(8)))
+                        .push(
+                            Container::new(
+                                Tooltip::new(
+                                    Text::new(tracked_time.date.format("%Y-%m-%d").to_string())
+                                        .size(16),
+                                    format!(
+                                        "Created: {}\nModified: {}{}",
+                                        tracked_time
+                                            .created_at
+                                            .with_timezone(&chrono::Local)
+                                            .format("%Y-%m-%d %H:%M"),
+                                        tracked_time
+                                            .modified_at
+                                            .with_timezone(&chrono::Local)
+                                            .format("%Y-%m-%d %H:%M"),
+                                        tracked_time
+                                            .id
+                                            .map(|id| format!("\nID: @{}", id))
+                                            .unwrap_or_default()
+                                    ),
+                                    tooltip::Position::FollowCursor,
+                                )
+                                .style(style::TooltipStyle),
+                            )
+                            .height(Length::Fill)
+                            .width(Length::Units(80))
+                            .center_y()
+                            .style(style::TextStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                        )
+                        .push(
+                            Container::new(
+                                Text::new(format!(
+                                    "{}:{:02}",
+                                    duration.num_hours(),
+                                    duration.num_minutes() % 60
+                                ))
+                                .size(28),
+                            )
+                            .height(Length::Fill)
+                            .center_y()
+                            .style(style::TextStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                        )
+                        .push(planned_label)
+                        .push(Space::with_width(Length::Units(12)))
+                        .push(project_label)
+                        .push(Space::with_width(Length::Units(8)))
+                        .push(
+                            Container::new(
+                                Tooltip::new(
+                                    Text::new(&tracked_time.description)
+                                        .size(24)
+                                        .width(Length::Fill),
+                                    &tracked_time.description,
+                                    tooltip::Position::FollowCursor,
+                                )
+                                .style(style::TooltipStyle),
+                            )
+                            .height(Length::Fill)
+                            .width(Length::Fill)
+                            .center_y()
+                            .style(style::TextStyle {
+                                is_dark_mode: self.is_dark_mode,
+                            }),
+                        )
+                        .push(
+                            Container::new(
+                                Text::new(
+                                    tracked_time
+                                        .tags
+                                        .iter()
+                                        .map(|tag| format!("#{}", tag))
+                                        .collect::<Vec<String>>()
+                                        .join(" "),
+                                )
+                                .size(16)
+                                .color(style::GRAY),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Units(8)))
+                        .push(
+                            Container::new(
+                                Button::new(
+                                    &mut row.edit_button,
+                                    Row::new()
+                                        .push(Space::with_width(Length::Units(8)))
+                                        .push(Text::new("Edit"))
+                                        .push(Space::with_width(Length::Units(8))),
+                                )
+                                .on_press(Message::EditTrackedTime(i))
+                                .width(Length::Shrink)
+                                .style(style::ButtonStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                    foreground: None,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Units(8)))
+                        .push(
+                            Container::new(
+                                Button::new(
+                                    &mut row.expand_button,
+                                    Row::new()
+                                        .push(Space::with_width(Length::Units(8)))
+                                        .push(Text::new(match row.is_expanded {
+                                            true => "Hide Sessions",
+                                            false => "Sessions",
+                                        }))
+                                        .push(Space::with_width(Length::Units(8))),
+                                )
+                                .on_press(Message::ToggleIntervals(i))
+                                .width(Length::Shrink)
+                                .style(style::ButtonStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                    foreground: None,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Units(8)))
+                        .push(
+                            Container::new(
+                                Button::new(
+                                    &mut row.copy_button,
+                                    Row::new()
+                                        .push(Space::with_width(Length::Units(8)))
+                                        .push(Text::new("Copy Text"))
+                                        .push(Space::with_width(Length::Units(8))),
                                 )
-                                .push(Rule::horizontal(8).style(style::RuleStyle))
-                                .height(Length::FillPortion(2)),
+                                .on_press(Message::CopyText(i))
+                                .width(Length::Shrink)
+                                .style(style::ButtonStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                    foreground: None,
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
                         )
+                        .push(Space::with_width(Length::Units(8)))
                         .push(
+                            Container::new(
+                                Button::new(
+                                    &mut row.delete_button,
+                                    Row::new()
+                                        .push(Space::with_width(Length::Units(8)))
+                                        .push(Text::new("Delete"))
+                                        .push(Space::with_width(Length::Units(8))),
+                                )
+                                .on_press(Message::DeleteTrackedTime(i))
+                                .width(Length::Shrink)
+                                .style(style::ButtonStyle {
+                                    is_dark_mode: self.is_dark_mode,
+                                    foreground: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
+                                }),
+                            )
+                            .height(Length::Fill)
+                            .center_y(),
+                        )
+                        .push(Space::with_width(Length::Units(8)))
+                        .width(Length::Fill),
+                )
+                .height(Length::Units(50))
+                .style(style::TrackedTimeStyle {
+                    is_dark_mode: self.is_dark_mode,
+                }),
+            );
+
+            if row.is_editing {
+                let edit_error: Element<Message> = match &row.edit_error {
+                    Some(error) => Container::new(Text::new(error).size(14))
+                        .height(Length::Fill)
+                        .center_y()
+                        .style(style::ValidationStyle)
+                        .into(),
+                    None => Space::with_width(Length::Shrink).into(),
+                };
+                entry = entry.push(
+                    Container::new(
+                        Row::new()
+                            .push(Space::with_width(Length::Units(58)))
+                            .push(
+                                Container::new(
+                                    TextInput::new(
+                                        &mut row.duration_text_input,
+                                        "duration",
+                                        &row.duration_input,
+                                        move |input| Message::EditDurationChanged(i, input),
+                                    )
+                                    .on_submit(Message::SaveEdit(i))
+                                    .padding(3)
+                                    .width(Length::Units(80))
+                                    .style(
+                                        style::TextInputStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(
+                                Container::new(
+                                    TextInput::new(
+                                        &mut row.description_text_input,
+                                        "description",
+                                        &row.description_input,
+                                        move |input| Message::EditDescriptionChanged(i, input),
+                                    )
+                                    .on_submit(Message::SaveEdit(i))
+                                    .padding(3)
+                                    .style(
+                                        style::TextInputStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .width(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(edit_error)
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(
+                                Container::new(
+                                    Button::new(
+                                        &mut row.save_edit_button,
+                                        Row::new()
+                                            .push(Space::with_width(Length::Units(8)))
+                                            .push(Text::new("Save"))
+                                            .push(Space::with_width(Length::Units(8))),
+                                    )
+                                    .on_press(Message::SaveEdit(i))
+                                    .style(
+                                        style::ButtonStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                            foreground: None,
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(
+                                Container::new(
+                                    Button::new(
+                                        &mut row.cancel_edit_button,
+                                        Row::new()
+                                            .push(Space::with_width(Length::Units(8)))
+                                            .push(Text::new("Cancel"))
+                                            .push(Space::with_width(Length::Units(8))),
+                                    )
+                                    .on_press(Message::CancelEdit(i))
+                                    .style(
+                                        style::ButtonStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                            foreground: None,
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .height(Length::Units(36))
+                    .style(style::TrackedTimeStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                );
+            }
+
+            if row.is_expanded {
+                for (j, (interval, interval_row)) in tracked_time
+                    .intervals
+                    .iter()
+                    .zip(row.intervals.iter_mut())
+                    .enumerate()
+                {
+                    let interval_duration = interval.duration();
+                    entry = entry.push(
+                        Container::new(
                             Row::new()
+                                .push(Space::with_width(Length::Units(58)))
                                 .push(
-                                    Container::new(Text::new("existing entry with number "))
+                                    Container::new(
+                                        TextInput::new(
+                                            &mut interval_row.start_text_input,
+                                            "start",
+                                            &interval_row.start_input,
+                                            move |input| Message::IntervalStartChanged(i, j, input),
+                                        )
+                                        .on_submit(Message::SaveInterval(i, j))
+                                        .padding(3)
+                                        .width(Length::Units(160))
+                                        .style(
+                                            style::TextInputStyle {
+                                                is_dark_mode: self.is_dark_mode,
+                                            },
+                                        ),
+                                    )
+                                    .height(Length::Fill)
+                                    .center_y(),
+                                )
+                                .push(
+                                    Container::new(Text::new(" - "))
                                         .height(Length::Fill)
                                         .center_y()
                                         .style(style::TextStyle {
@@ -469,168 +2814,658 @@ impl Application for SimpleTimeTracker {
                                 )
                                 .push(
                                     Container::new(
-                                        TextInput::new(
-                                            &mut self.index_text_input,
-                                            "#",
-                                            &self.index_input,
-                                            Message::IndexInputChanged,
+                                        TextInput::new(
+                                            &mut interval_row.end_text_input,
+                                            "end",
+                                            &interval_row.end_input,
+                                            move |input| Message::IntervalEndChanged(i, j, input),
+                                        )
+                                        .on_submit(Message::SaveInterval(i, j))
+                                        .padding(3)
+                                        .width(Length::Units(160))
+                                        .style(
+                                            style::TextInputStyle {
+                                                is_dark_mode: self.is_dark_mode,
+                                            },
+                                        ),
+                                    )
+                                    .height(Length::Fill)
+                                    .center_y(),
+                                )
+                                .push(Space::with_width(Length::Units(12)))
+                                .push(
+                                    Container::new(Text::new(format!(
+                                        "{}:{:02}:{:02} ({})",
+                                        interval_duration.num_hours(),
+                                        interval_duration.num_minutes() % 60,
+                                        interval_duration.num_seconds() % 60,
+                                        interval.source.as_str()
+                                    )))
+                                    .height(Length::Fill)
+                                    .width(Length::Fill)
+                                    .center_y()
+                                    .style(
+                                        style::TextStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                        },
+                                    ),
+                                )
+                                .push(
+                                    Container::new(
+                                        Button::new(
+                                            &mut interval_row.save_button,
+                                            Row::new()
+                                                .push(Space::with_width(Length::Units(8)))
+                                                .push(Text::new("Save"))
+                                                .push(Space::with_width(Length::Units(8))),
                                         )
-                                        .on_submit(Message::ApplyOperation)
+                                        .on_press(Message::SaveInterval(i, j))
                                         .padding(3)
-                                        .width(Length::Units(30))
                                         .style(
-                                            style::TextInputStyle {
+                                            style::ButtonStyle {
+                                                is_dark_mode: self.is_dark_mode,
+                                                foreground: None,
+                                            },
+                                        ),
+                                    )
+                                    .height(Length::Fill)
+                                    .center_y(),
+                                )
+                                .push(Space::with_width(Length::Units(8)))
+                                .push(
+                                    Container::new(
+                                        Button::new(
+                                            &mut interval_row.delete_button,
+                                            Row::new()
+                                                .push(Space::with_width(Length::Units(8)))
+                                                .push(Text::new("Delete"))
+                                                .push(Space::with_width(Length::Units(8))),
+                                        )
+                                        .on_press(Message::DeleteInterval(i, j))
+                                        .padding(3)
+                                        .style(
+                                            style::ButtonStyle {
                                                 is_dark_mode: self.is_dark_mode,
+                                                foreground: Color::from_rgb8(0xc8, 0x40, 0x00)
+                                                    .into(),
                                             },
                                         ),
                                     )
                                     .height(Length::Fill)
                                     .center_y(),
                                 )
-                                .height(Length::FillPortion(3)),
+                                .push(Space::with_width(Length::Units(8)))
+                                .width(Length::Fill),
+                        )
+                        .height(Length::Units(34))
+                        .style(style::TrackedTimeStyle {
+                            is_dark_mode: self.is_dark_mode,
+                        }),
+                    );
+                }
+
+                entry = entry.push(
+                    Row::new().push(Space::with_width(Length::Units(58))).push(
+                        Button::new(
+                            &mut row.add_interval_button,
+                            Row::new()
+                                .push(Space::with_width(Length::Units(8)))
+                                .push(Text::new("Add Session"))
+                                .push(Space::with_width(Length::Units(8))),
                         )
-                        .width(Length::FillPortion(2)),
+                        .on_press(Message::AddInterval(i))
+                        .padding(3)
+                        .style(style::ButtonStyle {
+                            is_dark_mode: self.is_dark_mode,
+                            foreground: None,
+                        }),
+                    ),
+                );
+            }
+
+            tracked_times_list = tracked_times_list.push(entry);
+        }
+
+        let tracked_times = Container::new(tracked_times_list).width(Length::Fill);
+
+        self.tag_filter_buttons
+            .resize_with(tags.len(), button::State::new);
+        let mut tag_filter_bar = Row::new().spacing(4);
+        if !tags.is_empty() {
+            tag_filter_bar = tag_filter_bar.push(
+                Container::new(Text::new("Tags:"))
+                    .height(Length::Fill)
+                    .center_y()
+                    .style(style::TextStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+            );
+        }
+        for (tag, tag_button) in tags.into_iter().zip(self.tag_filter_buttons.iter_mut()) {
+            let is_selected = self.tag_filter.contains(&tag);
+            tag_filter_bar = tag_filter_bar.push(
+                Container::new(
+                    Button::new(tag_button, Text::new(format!("#{}", tag)))
+                        .on_press(Message::ToggleTagFilter(tag))
+                        .padding(3)
+                        .style(style::ButtonStyle {
+                            is_dark_mode: self.is_dark_mode,
+                            foreground: if is_selected {
+                                Some(style::PRIMARY)
+                            } else {
+                                None
+                            },
+                        }),
                 )
-                .push(
-                    Row::new()
-                        .push(Space::with_width(Length::Units(24)))
-                        .push(
-                            Container::new(
-                                Button::new(
-                                    &mut self.apply_operation_button,
-                                    Row::new()
-                                        .push(Space::with_width(Length::Units(12)))
-                                        .push(Text::new("Apply"))
-                                        .push(Space::with_width(Length::Units(12))),
+                .height(Length::Fill)
+                .center_y(),
+            );
+        }
+        if !self.tag_filter.is_empty() {
+            tag_filter_bar = tag_filter_bar.push(
+                Container::new(
+                    Button::new(&mut self.clear_tag_filter_button, Text::new("Show all"))
+                        .on_press(Message::ClearTagFilter)
+                        .padding(3)
+                        .style(style::ButtonStyle {
+                            is_dark_mode: self.is_dark_mode,
+                            foreground: None,
+                        }),
+                )
+                .height(Length::Fill)
+                .center_y(),
+            );
+        }
+        let list_header = Container::new(
+            tag_filter_bar.push(Space::with_width(Length::Fill)).push(
+                Container::new(Text::new(format!(
+                    "Total: {}:{:02}",
+                    total.num_hours(),
+                    total.num_minutes() % 60
+                )))
+                .height(Length::Fill)
+                .center_y()
+                .style(style::TextStyle {
+                    is_dark_mode: self.is_dark_mode,
+                }),
+            ),
+        )
+        .height(Length::Units(30))
+        .width(Length::Fill);
+
+        let mut projects_panel = Column::new();
+        if self.show_projects {
+            let mut projects_list = Column::new().spacing(4);
+            let tracker = &self.tracker;
+            for (i, (project, delete_button)) in tracker
+                .projects
+                .iter()
+                .zip(self.project_delete_buttons.iter_mut())
+                .enumerate()
+            {
+                let client = project.client_id.and_then(|id| tracker.client(id));
+                projects_list = projects_list.push(
+                    Container::new(
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(
+                                Container::new(
+                                    Container::new(Text::new(&project.name).size(16))
+                                        .padding(3)
+                                        .style(style::ProjectLabelStyle {
+                                            color: style::project_color(project.color),
+                                        }),
                                 )
-                                .on_press(Message::ApplyOperation)
-                                .padding(3)
-                                .style(style::ButtonStyle {
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(12)))
+                            .push(
+                                Container::new(Text::new(match client {
+                                    Some(client) => client.name.clone(),
+                                    None => String::from("No client"),
+                                }))
+                                .height(Length::Fill)
+                                .width(Length::Fill)
+                                .center_y()
+                                .style(style::TextStyle {
                                     is_dark_mode: self.is_dark_mode,
-                                    foreground: None,
                                 }),
                             )
-                            .height(Length::Fill)
-                            .center_y(),
-                        )
-                        .push(Space::with_width(Length::Fill))
-                        .width(Length::FillPortion(1)),
-                )
-                .height(Length::Units(80)),
-        )
-        .padding(8)
-        .width(Length::Fill)
-        .center_x();
-
-        let mut tracked_times_list = Column::new().spacing(6);
+                            .push(
+                                Container::new(
+                                    Button::new(
+                                        delete_button,
+                                        Row::new()
+                                            .push(Space::with_width(Length::Units(8)))
+                                            .push(Text::new("Delete"))
+                                            .push(Space::with_width(Length::Units(8))),
+                                    )
+                                    .on_press(Message::DeleteProject(i))
+                                    .padding(3)
+                                    .style(
+                                        style::ButtonStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                            foreground: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .height(Length::Units(34))
+                    .style(style::TrackedTimeStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                );
+            }
 
-        for (i, tracked_time) in self.tracked_times.iter_mut().enumerate() {
-            tracked_times_list = tracked_times_list.push(
-                Container::new(
+            projects_panel = projects_panel
+                .push(Space::with_height(Length::Units(12)))
+                .push(
                     Row::new()
                         .push(
-                            Container::new(
-                                Row::new()
-                                    .push(Space::with_width(Length::Units(4)))
-                                    .push(Text::new(format!("{}", i + 1)).size(28))
-                                    .push(Space::with_width(Length::Units(4))),
+                            TextInput::new(
+                                &mut self.project_name_text_input,
+                                "project",
+                                &self.project_name_input,
+                                Message::ProjectNameInputChanged,
                             )
-                            .height(Length::Fill)
-                            .width(Length::Units(50))
-                            .center_x()
-                            .center_y()
-                            .style(style::IndexStyle {
+                            .on_submit(Message::AddProject)
+                            .padding(3)
+                            .style(style::TextInputStyle {
                                 is_dark_mode: self.is_dark_mode,
                             }),
                         )
                         .push(Space::with_width(Length::Units(8)))
                         .push(
-                            Container::new(
-                                Text::new(format!(
-                                    "{}:{:02}",
-                                    tracked_time.duration.num_hours(),
-                                    tracked_time.duration.num_minutes() % 60
-                                ))
-                                .size(28),
+                            TextInput::new(
+                                &mut self.client_name_text_input,
+                                "client (optional)",
+                                &self.client_name_input,
+                                Message::ClientNameInputChanged,
                             )
-                            .height(Length::Fill)
-                            .center_y()
-                            .style(style::TextStyle {
+                            .on_submit(Message::AddProject)
+                            .padding(3)
+                            .style(style::TextInputStyle {
                                 is_dark_mode: self.is_dark_mode,
                             }),
                         )
-                        .push(Space::with_width(Length::Units(12)))
+                        .push(Space::with_width(Length::Units(8)))
                         .push(
-                            Container::new(
-                                Tooltip::new(
-                                    Text::new(&tracked_time.description)
-                                        .size(24)
-                                        .width(Length::Fill),
-                                    &tracked_time.description,
-                                    tooltip::Position::FollowCursor,
-                                )
-                                .style(style::TooltipStyle),
+                            Button::new(
+                                &mut self.add_project_button,
+                                Row::new()
+                                    .push(Space::with_width(Length::Units(8)))
+                                    .push(Text::new("Add Project"))
+                                    .push(Space::with_width(Length::Units(8))),
                             )
-                            .height(Length::Fill)
-                            .width(Length::Fill)
-                            .center_y()
-                            .style(style::TextStyle {
+                            .on_press(Message::AddProject)
+                            .padding(3)
+                            .style(style::ButtonStyle {
                                 is_dark_mode: self.is_dark_mode,
+                                foreground: None,
                             }),
+                        ),
+                )
+                .push(Space::with_height(Length::Units(8)))
+                .push(projects_list);
+        }
+
+        let mut export_panel = Column::new();
+        if self.show_export {
+            let mut export_row = Row::new()
+                .push(
+                    PickList::new(
+                        &mut self.export_format_pick_list,
+                        &ExportFormat::ALL[..],
+                        Some(self.export_format),
+                        Message::ExportFormatSelected,
+                    )
+                    .padding(3)
+                    .width(Length::Units(130))
+                    .style(style::PickListStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                )
+                .push(Space::with_width(Length::Units(8)))
+                .push(
+                    TextInput::new(
+                        &mut self.export_path_text_input,
+                        "file",
+                        &self.export_path_input,
+                        Message::ExportPathInputChanged,
+                    )
+                    .on_submit(Message::Export)
+                    .padding(3)
+                    .style(style::TextInputStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                );
+            // The JSON export always contains everything
+            if self.export_format != ExportFormat::Json {
+                export_row = export_row
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(
+                        TextInput::new(
+                            &mut self.export_from_text_input,
+                            "from YYYY-MM-DD",
+                            &self.export_from_input,
+                            Message::ExportFromInputChanged,
                         )
-                        .push(
-                            Container::new(
-                                Button::new(
-                                    &mut tracked_time.copy_button,
-                                    Row::new()
-                                        .push(Space::with_width(Length::Units(8)))
-                                        .push(Text::new("Copy Text"))
-                                        .push(Space::with_width(Length::Units(8))),
-                                )
-                                .on_press(Message::CopyText(i))
-                                .width(Length::Shrink)
-                                .style(style::ButtonStyle {
-                                    is_dark_mode: self.is_dark_mode,
-                                    foreground: None,
-                                }),
-                            )
-                            .height(Length::Fill)
-                            .center_y(),
-                        )
-                        .push(Space::with_width(Length::Units(8)))
-                        .push(
-                            Container::new(
-                                Button::new(
-                                    &mut tracked_time.delete_button,
-                                    Row::new()
-                                        .push(Space::with_width(Length::Units(8)))
-                                        .push(Text::new("Delete"))
-                                        .push(Space::with_width(Length::Units(8))),
-                                )
-                                .on_press(Message::DeleteTrackedTime(i))
-                                .width(Length::Shrink)
-                                .style(style::ButtonStyle {
-                                    is_dark_mode: self.is_dark_mode,
-                                    foreground: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
-                                }),
-                            )
-                            .height(Length::Fill)
-                            .center_y(),
+                        .on_submit(Message::Export)
+                        .padding(3)
+                        .width(Length::Units(130))
+                        .style(style::TextInputStyle {
+                            is_dark_mode: self.is_dark_mode,
+                        }),
+                    )
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(
+                        TextInput::new(
+                            &mut self.export_to_text_input,
+                            "to YYYY-MM-DD",
+                            &self.export_to_input,
+                            Message::ExportToInputChanged,
                         )
+                        .on_submit(Message::Export)
+                        .padding(3)
+                        .width(Length::Units(130))
+                        .style(style::TextInputStyle {
+                            is_dark_mode: self.is_dark_mode,
+                        }),
+                    );
+            }
+            export_row = export_row.push(Space::with_width(Length::Units(8))).push(
+                Button::new(
+                    &mut self.export_file_button,
+                    Row::new()
                         .push(Space::with_width(Length::Units(8)))
-                        .width(Length::Fill),
+                        .push(Text::new("Export"))
+                        .push(Space::with_width(Length::Units(8))),
                 )
-                .height(Length::Units(50))
-                .style(style::TrackedTimeStyle {
+                .on_press(Message::Export)
+                .padding(3)
+                .style(style::ButtonStyle {
                     is_dark_mode: self.is_dark_mode,
+                    foreground: None,
                 }),
             );
+
+            export_panel = export_panel
+                .push(Space::with_height(Length::Units(12)))
+                .push(export_row);
+            if self.export_format == ExportFormat::Csv {
+                let mut columns = Row::new().spacing(12);
+                for column in csv::Column::ALL.iter().copied() {
+                    columns = columns.push(
+                        Checkbox::new(
+                            self.export_columns.contains(&column),
+                            column.name(),
+                            move |is_checked| Message::ExportColumnToggled(column, is_checked),
+                        )
+                        .size(16)
+                        .spacing(4)
+                        .style(style::CheckboxStyle {
+                            is_dark_mode: self.is_dark_mode,
+                        }),
+                    );
+                }
+                export_panel = export_panel
+                    .push(Space::with_height(Length::Units(8)))
+                    .push(Container::new(columns).style(style::TextStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }));
+            }
+            let mut import_row = Row::new()
+                .push(
+                    PickList::new(
+                        &mut self.import_format_pick_list,
+                        &ImportFormat::ALL[..],
+                        Some(self.import_format),
+                        Message::ImportFormatSelected,
+                    )
+                    .padding(3)
+                    .width(Length::Units(130))
+                    .style(style::PickListStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                )
+                .push(Space::with_width(Length::Units(8)))
+                .push(
+                    TextInput::new(
+                        &mut self.import_path_text_input,
+                        "file to import",
+                        &self.import_path_input,
+                        Message::ImportPathInputChanged,
+                    )
+                    .on_submit(Message::PreviewImport)
+                    .padding(3)
+                    .style(style::TextInputStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }),
+                )
+                .push(Space::with_width(Length::Units(8)))
+                .push(
+                    Button::new(
+                        &mut self.import_preview_button,
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(Text::new("Preview"))
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .on_press(Message::PreviewImport)
+                    .padding(3)
+                    .style(style::ButtonStyle {
+                        is_dark_mode: self.is_dark_mode,
+                        foreground: None,
+                    }),
+                )
+                .push(Space::with_width(Length::Units(8)))
+                .push(
+                    Button::new(
+                        &mut self.import_merge_button,
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(Text::new("Merge"))
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .on_press(Message::Import(ImportMode::Merge))
+                    .padding(3)
+                    .style(style::ButtonStyle {
+                        is_dark_mode: self.is_dark_mode,
+                        foreground: None,
+                    }),
+                );
+            // Other formats only hold entries, replacing everything with them would lose projects
+            if self.import_format == ImportFormat::Json {
+                import_row = import_row.push(Space::with_width(Length::Units(8))).push(
+                    Button::new(
+                        &mut self.import_replace_button,
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(Text::new("Replace all"))
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .on_press(Message::Import(ImportMode::Replace))
+                    .padding(3)
+                    .style(style::ButtonStyle {
+                        is_dark_mode: self.is_dark_mode,
+                        foreground: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
+                    }),
+                );
+            }
+            export_panel = export_panel
+                .push(Space::with_height(Length::Units(8)))
+                .push(import_row);
+
+            if let Some(preview) = &self.import_preview {
+                let mut preview_list = Column::new().spacing(2);
+                for tracked_time in preview.added.iter().take(IMPORT_PREVIEW_LENGTH) {
+                    let project = tracked_time
+                        .project_id
+                        .and_then(|id| preview.projects.iter().find(|project| project.id == id))
+                        .map(|project| format!("  [{}]", project.name))
+                        .unwrap_or_default();
+                    preview_list = preview_list.push(
+                        Text::new(format!(
+                            "{}  {:>6}{}  {}",
+                            tracked_time.date,
+                            model::format_duration(tracked_time.duration()),
+                            project,
+                            tracked_time.description
+                        ))
+                        .size(16),
+                    );
+                }
+                if preview.added.len() > IMPORT_PREVIEW_LENGTH {
+                    preview_list = preview_list.push(
+                        Text::new(format!(
+                            "and {} more",
+                            preview.added.len() - IMPORT_PREVIEW_LENGTH
+                        ))
+                        .size(16)
+                        .color(style::GRAY),
+                    );
+                }
+                preview_list = preview_list.push(Text::new(format!(
+                    "Would add {} entries, {} are already stored",
+                    preview.added.len(),
+                    preview.duplicates
+                )));
+                export_panel = export_panel
+                    .push(Space::with_height(Length::Units(8)))
+                    .push(Container::new(preview_list).style(style::TextStyle {
+                        is_dark_mode: self.is_dark_mode,
+                    }));
+            }
         }
 
-        let tracked_times = Container::new(tracked_times_list).width(Length::Fill);
+        let mut content = Column::new();
+        if let Some(storage_error) = &self.storage_error {
+            let action = match storage_error.operation {
+                StorageOperation::Load => "load data",
+                StorageOperation::StoreSettings => "save settings",
+                StorageOperation::StoreChanges => "save timers and tracked times",
+                StorageOperation::StoreProjects => "save projects",
+            };
+            content = content
+                .push(
+                    Container::new(
+                        Row::new()
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(
+                                Container::new(Text::new(format!(
+                                    "Could not {}: {}",
+                                    action, storage_error.error
+                                )))
+                                .height(Length::Fill)
+                                .width(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(
+                                Container::new(
+                                    Button::new(
+                                        &mut self.retry_button,
+                                        Row::new()
+                                            .push(Space::with_width(Length::Units(8)))
+                                            .push(Text::new("Retry"))
+                                            .push(Space::with_width(Length::Units(8))),
+                                    )
+                                    .on_press(Message::RetryStorage)
+                                    .padding(3)
+                                    .style(
+                                        style::ButtonStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                            foreground: None,
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(
+                                Container::new(
+                                    Button::new(
+                                        &mut self.dismiss_error_button,
+                                        Row::new()
+                                            .push(Space::with_width(Length::Units(8)))
+                                            .push(Text::new("Dismiss"))
+                                            .push(Space::with_width(Length::Units(8))),
+                                    )
+                                    .on_press(Message::DismissStorageError)
+                                    .padding(3)
+                                    .style(
+                                        style::ButtonStyle {
+                                            is_dark_mode: self.is_dark_mode,
+                                            foreground: None,
+                                        },
+                                    ),
+                                )
+                                .height(Length::Fill)
+                                .center_y(),
+                            )
+                            .push(Space::with_width(Length::Units(8))),
+                    )
+                    .height(Length::Units(40))
+                    .width(Length::Fill)
+                    .style(style::ErrorStyle),
+                )
+                .push(Space::with_height(Length::Units(12)));
+        }
+
+        let toast: Element<Message> = match &self.toast {
+            Some(toast) => {
+                let undo_button: Element<Message> = match toast.can_undo {
+                    true => Container::new(
+                        Button::new(
+                            &mut self.undo_toast_button,
+                            Row::new()
+                                .push(Space::with_width(Length::Units(8)))
+                                .push(Text::new("Undo"))
+                                .push(Space::with_width(Length::Units(8))),
+                        )
+                        .on_press(Message::Undo)
+                        .padding(3)
+                        .style(style::ButtonStyle {
+                            is_dark_mode: true,
+                            foreground: Some(style::PRIMARY),
+                        }),
+                    )
+                    .height(Length::Fill)
+                    .center_y()
+                    .into(),
+                    false => Space::with_width(Length::Shrink).into(),
+                };
+                Column::new()
+                    .push(Space::with_height(Length::Units(8)))
+                    .push(
+                        Container::new(
+                            Row::new()
+                                .push(Space::with_width(Length::Units(8)))
+                                .push(
+                                    Container::new(Text::new(&toast.message))
+                                        .height(Length::Fill)
+                                        .width(Length::Fill)
+                                        .center_y(),
+                                )
+                                .push(undo_button)
+                                .push(Space::with_width(Length::Units(8))),
+                        )
+                        .height(Length::Units(40))
+                        .width(Length::Fill)
+                        .style(style::ToastStyle),
+                    )
+                    .into()
+            }
+            None => Space::with_height(Length::Shrink).into(),
+        };
 
         Container::new(
-            Column::new()
+            content
                 .push(
                     Container::new(
                         Row::new()
@@ -640,19 +3475,36 @@ impl Application for SimpleTimeTracker {
                             .push(Space::with_width(Length::Units(8)))
                             .push(clear_button)
                             .push(Space::with_width(Length::Units(8)))
-                            .push(dark_mode_button),
+                            .push(pomodoro_button)
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(dark_mode_button)
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(projects_button)
+                            .push(Space::with_width(Length::Units(8)))
+                            .push(export_button),
                     )
                     .width(Length::Fill)
                     .center_x(),
                 )
+                .push(Space::with_height(Length::Units(8)))
+                .push(match self.is_pomodoro_mode {
+                    true => pomodoro_bar,
+                    false => timers_bar,
+                })
+                .push(projects_panel)
+                .push(export_panel)
                 .push(Space::with_height(Length::Units(12)))
                 .push(timer_operations)
-                .push(Space::with_height(Length::Units(20)))
+                .push(Space::with_height(Length::Units(12)))
+                .push(list_header)
+                .push(Space::with_height(Length::Units(8)))
                 .push(
                     Scrollable::new(&mut self.tracked_times_scroll)
                         .push(tracked_times)
-                        .width(Length::Fill),
-                ),
+                        .width(Length::Fill)
+                        .height(Length::Fill),
+                )
+                .push(toast),
         )
         .width(Length::Fill)
         .height(Length::Fill)
@@ -0,0 +1,383 @@
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use crate::database::{Error, Result};
+use crate::model::IntervalSource;
+use crate::timer::DEFAULT_TIMER_NAME;
+
+// Keys of the single timer's state before timers got their own table
+const RUNNING_SINCE_KEY: &str = "running_since";
+const PAUSED_SECONDS_KEY: &str = "paused_seconds";
+
+// Each step upgrades the schema by one version, PRAGMA user_version stores the applied count.
+// Never change a released step, append a new one instead.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    create_initial_schema,
+    add_tracked_time_timestamps,
+    add_intervals,
+    split_time_state,
+    add_projects,
+    add_tags,
+    add_timers,
+    add_time_boxes,
+];
+
+pub fn run(db: &mut Connection, path: &Path) -> Result<()> {
+    let version: i64 = db.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    let latest = MIGRATIONS.len() as i64;
+    if version > latest {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    if version == latest {
+        return Ok(());
+    }
+
+    backup(db, path, &format!("v{}", version))?;
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let transaction = db.transaction()?;
+        migration(&transaction)?;
+        transaction.pragma_update(None, "user_version", &(i as i64 + 1))?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+// Copies the database next to it before it is changed as a whole, nothing is copied while it is empty
+pub(crate) fn backup(db: &Connection, path: &Path, label: &str) -> Result<()> {
+    let table_count: i64 =
+        db.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0))?;
+    if table_count == 0 {
+        return Ok(());
+    }
+
+    let backup_path = format!(
+        "{}.{}-{}.bak",
+        path.display(),
+        label,
+        chrono::Local::now().format("%Y%m%d%H%M%S%3f")
+    );
+    db.execute("VACUUM INTO ?1", params![backup_path])?;
+    Ok(())
+}
+
+// Schema as it was before versioning, existing databases already have these tables
+fn create_initial_schema(db: &Connection) -> Result<()> {
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS States (
+                Key TEXT PRIMARY KEY,
+                Value INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS TrackedTimes (
+                ID INTEGER PRIMARY KEY,
+                Seconds INTEGER NOT NULL,
+                Description TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_tracked_time_timestamps(db: &Connection) -> Result<()> {
+    add_missing_column(db, "TrackedTimes", "Date", "TEXT")?;
+    add_missing_column(db, "TrackedTimes", "Created", "INTEGER")?;
+    add_missing_column(db, "TrackedTimes", "Modified", "INTEGER")?;
+
+    // The actual dates of existing entries are unknown, so they are dated to the upgrade
+    db.execute(
+        "UPDATE TrackedTimes SET Date = ?1, Created = ?2, Modified = ?2 WHERE Created IS NULL",
+        params![
+            chrono::Local::today()
+                .naive_local()
+                .format("%Y-%m-%d")
+                .to_string(),
+            chrono::Utc::now().timestamp()
+        ],
+    )?;
+    Ok(())
+}
+
+fn add_intervals(db: &Connection) -> Result<()> {
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS Intervals (
+                ID INTEGER PRIMARY KEY,
+                TrackedTimeID INTEGER NOT NULL REFERENCES TrackedTimes(ID) ON DELETE CASCADE,
+                Start INTEGER NOT NULL,
+                End INTEGER NOT NULL,
+                Source TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Entries stored before intervals existed get a single session ending at their creation
+    db.execute(
+        "INSERT INTO Intervals (TrackedTimeID, Start, End, Source)
+            SELECT ID, Created - Seconds, Created, ?1 FROM TrackedTimes
+            WHERE Seconds > 0 AND ID NOT IN (SELECT TrackedTimeID FROM Intervals)",
+        params![IntervalSource::Legacy.as_str()],
+    )?;
+    Ok(())
+}
+
+// Older versions stored either the start timestamp or the paused duration under one key
+fn split_time_state(db: &Connection) -> Result<()> {
+    db.execute(
+        "UPDATE States SET Key = ?1 WHERE Key = 'time'
+            AND EXISTS (SELECT 1 FROM States WHERE Key = 'paused' AND Value = 0)",
+        params![RUNNING_SINCE_KEY],
+    )?;
+    db.execute(
+        "UPDATE States SET Key = ?1 WHERE Key = 'time'",
+        params![PAUSED_SECONDS_KEY],
+    )?;
+    db.execute("DELETE FROM States WHERE Key = 'paused'", [])?;
+    Ok(())
+}
+
+fn add_projects(db: &Connection) -> Result<()> {
+    db.execute(
+        "CREATE TABLE Clients (
+                ID INTEGER PRIMARY KEY,
+                Name TEXT NOT NULL UNIQUE
+        )",
+        [],
+    )?;
+    db.execute(
+        "CREATE TABLE Projects (
+                ID INTEGER PRIMARY KEY,
+                Name TEXT NOT NULL,
+                ClientID INTEGER REFERENCES Clients(ID),
+                Color INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    add_missing_column(
+        db,
+        "TrackedTimes",
+        "ProjectID",
+        "INTEGER REFERENCES Projects(ID)",
+    )?;
+    Ok(())
+}
+
+fn add_tags(db: &Connection) -> Result<()> {
+    db.execute(
+        "CREATE TABLE Tags (
+                ID INTEGER PRIMARY KEY,
+                Name TEXT NOT NULL UNIQUE
+        )",
+        [],
+    )?;
+    db.execute(
+        "CREATE TABLE TrackedTimeTags (
+                TrackedTimeID INTEGER NOT NULL REFERENCES TrackedTimes(ID) ON DELETE CASCADE,
+                TagID INTEGER NOT NULL REFERENCES Tags(ID) ON DELETE CASCADE,
+                PRIMARY KEY (TrackedTimeID, TagID)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// The existing timer becomes the first one, a new database starts with one paused timer
+fn add_timers(db: &Connection) -> Result<()> {
+    db.execute(
+        "CREATE TABLE Timers (
+                ID INTEGER PRIMARY KEY,
+                Name TEXT NOT NULL,
+                RunningSince INTEGER,
+                PausedSeconds INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    db.execute(
+        "INSERT INTO Timers (Name, RunningSince, PausedSeconds) VALUES (?1,
+            (SELECT Value FROM States WHERE Key = ?2),
+            COALESCE((SELECT Value FROM States WHERE Key = ?3), 0))",
+        params![DEFAULT_TIMER_NAME, RUNNING_SINCE_KEY, PAUSED_SECONDS_KEY],
+    )?;
+    db.execute(
+        "DELETE FROM States WHERE Key IN (?1, ?2)",
+        params![RUNNING_SINCE_KEY, PAUSED_SECONDS_KEY],
+    )?;
+    Ok(())
+}
+
+fn add_time_boxes(db: &Connection) -> Result<()> {
+    add_missing_column(db, "Timers", "TimeBoxSeconds", "INTEGER")?;
+    add_missing_column(db, "TrackedTimes", "PlannedSeconds", "INTEGER")?;
+    Ok(())
+}
+
+fn add_missing_column(db: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = db.prepare(&format!(
+        "SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1",
+        table
+    ))?;
+    if !stmt.exists(params![column])? {
+        db.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // Each test works in a directory of its own, as backups are written next to the database
+    fn database_path(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "simple_time_tracker_migrations_{}_{}",
+            test,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory.join("simple_time_tracker.sqlite")
+    }
+
+    // The schema before versioning, with the timer stored under 'time' and 'paused'
+    fn create_baseline(path: &Path, time: i64, paused: i64) -> Connection {
+        let db = Connection::open(path).unwrap();
+        db.execute_batch(
+            "CREATE TABLE States (Key TEXT PRIMARY KEY, Value INTEGER NOT NULL);
+            CREATE TABLE TrackedTimes (
+                ID INTEGER PRIMARY KEY,
+                Seconds INTEGER NOT NULL,
+                Description TEXT NOT NULL
+            );
+            INSERT INTO TrackedTimes (ID, Seconds, Description) VALUES
+                (1, 3600, 'Review'), (2, 0, 'Nothing yet');
+            INSERT INTO States (Key, Value) VALUES ('darkmode', 0);",
+        )
+        .unwrap();
+        db.execute(
+            "INSERT INTO States (Key, Value) VALUES ('time', ?1), ('paused', ?2)",
+            params![time, paused],
+        )
+        .unwrap();
+        db
+    }
+
+    fn backups(path: &Path) -> Vec<String> {
+        fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".bak"))
+            .collect()
+    }
+
+    fn version(db: &Connection) -> i64 {
+        db.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn timer(db: &Connection) -> (String, Option<i64>, i64) {
+        db.query_row(
+            "SELECT Name, RunningSince, PausedSeconds FROM Timers",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn migrates_a_running_timer_and_its_entries() {
+        let path = database_path("running");
+        let mut db = create_baseline(&path, 1_600_000_000, 0);
+
+        run(&mut db, &path).unwrap();
+
+        assert_eq!(version(&db), MIGRATIONS.len() as i64);
+        assert_eq!(
+            timer(&db),
+            (String::from(DEFAULT_TIMER_NAME), Some(1_600_000_000), 0)
+        );
+        let keys = db
+            .prepare("SELECT Key FROM States ORDER BY Key")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(keys, vec![String::from("darkmode")]);
+
+        // Only the entry with time gets a session, which ends when the entry was dated
+        let intervals = db
+            .prepare("SELECT TrackedTimeID, End - Start, Source FROM Intervals")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<(i64, i64, String)>>>()
+            .unwrap();
+        assert_eq!(
+            intervals,
+            vec![(1, 3600, String::from(IntervalSource::Legacy.as_str()))]
+        );
+        let undated: i64 = db
+            .query_row(
+                "SELECT COUNT(*) FROM TrackedTimes WHERE Date IS NULL OR Created IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(undated, 0);
+
+        let backups = backups(&path);
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].starts_with("simple_time_tracker.sqlite.v0-"));
+
+        // Nothing is left to migrate or back up the next time
+        run(&mut db, &path).unwrap();
+        assert_eq!(self::backups(&path).len(), 1);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn migrates_a_paused_timer() {
+        let path = database_path("paused");
+        let mut db = create_baseline(&path, 1234, 1);
+
+        run(&mut db, &path).unwrap();
+
+        assert_eq!(version(&db), MIGRATIONS.len() as i64);
+        assert_eq!(timer(&db), (String::from(DEFAULT_TIMER_NAME), None, 1234));
+        assert_eq!(backups(&path).len(), 1);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn creates_a_new_database_without_backup() {
+        let path = database_path("new");
+        let mut db = Connection::open(&path).unwrap();
+
+        run(&mut db, &path).unwrap();
+
+        assert_eq!(version(&db), MIGRATIONS.len() as i64);
+        assert_eq!(timer(&db), (String::from(DEFAULT_TIMER_NAME), None, 0));
+        assert!(backups(&path).is_empty());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn refuses_a_newer_database() {
+        let path = database_path("newer");
+        let mut db = Connection::open(&path).unwrap();
+        let newer = MIGRATIONS.len() as i64 + 1;
+        db.pragma_update(None, "user_version", &newer).unwrap();
+
+        assert!(matches!(
+            run(&mut db, &path),
+            Err(Error::UnsupportedVersion(version)) if version == newer
+        ));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
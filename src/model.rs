@@ -0,0 +1,208 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedTime {
+    pub id: Option<i64>,
+    pub description: String,
+    pub project_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub intervals: Vec<Interval>,
+    // Time box of the timer the time came from
+    pub planned: Option<chrono::Duration>,
+    pub date: chrono::NaiveDate,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub modified_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TrackedTime {
+    pub fn new(
+        description: String,
+        intervals: Vec<Interval>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        TrackedTime::with_timestamps(
+            description,
+            intervals,
+            now.with_timezone(&chrono::Local).date().naive_local(),
+            now,
+            now,
+        )
+    }
+
+    pub fn with_timestamps(
+        description: String,
+        intervals: Vec<Interval>,
+        date: chrono::NaiveDate,
+        created_at: chrono::DateTime<chrono::Utc>,
+        modified_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        TrackedTime {
+            id: None,
+            description,
+            project_id: None,
+            tags: Vec::new(),
+            intervals,
+            planned: None,
+            date,
+            created_at,
+            modified_at,
+        }
+    }
+
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+
+    pub fn duration(&self) -> chrono::Duration {
+        self.intervals
+            .iter()
+            .fold(chrono::Duration::zero(), |total, interval| {
+                total + interval.duration()
+            })
+    }
+
+    // Time left of the planned time, negative if the entry ran over
+    pub fn remaining(&self) -> Option<chrono::Duration> {
+        self.planned.map(|planned| planned - self.duration())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub client_id: Option<i64>,
+    // 0xRRGGBB
+    pub color: u32,
+}
+
+// Colors given to new projects in turn
+pub const PROJECT_COLORS: [u32; 8] = [
+    0x3a86ff, 0xe63946, 0x2a9d8f, 0x9b5de5, 0xf4a261, 0x00b4d8, 0xd81159, 0x8ac926,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalSource {
+    Timer,
+    Manual,
+    Legacy,
+    Pomodoro,
+    // Read from another time tracker's files
+    Imported,
+}
+
+impl IntervalSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntervalSource::Timer => "timer",
+            IntervalSource::Manual => "manual",
+            IntervalSource::Legacy => "legacy",
+            IntervalSource::Pomodoro => "pomodoro",
+            IntervalSource::Imported => "imported",
+        }
+    }
+
+    pub fn parse(source: &str) -> Self {
+        match source {
+            "timer" => IntervalSource::Timer,
+            "legacy" => IntervalSource::Legacy,
+            "pomodoro" => IntervalSource::Pomodoro,
+            "imported" => IntervalSource::Imported,
+            _ => IntervalSource::Manual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub id: Option<i64>,
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub end: chrono::DateTime<chrono::Utc>,
+    pub source: IntervalSource,
+}
+
+impl Interval {
+    pub fn new(
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+        source: IntervalSource,
+    ) -> Self {
+        Interval {
+            id: None,
+            start,
+            end,
+            source,
+        }
+    }
+
+    pub fn duration(&self) -> chrono::Duration {
+        self.end - self.start
+    }
+}
+
+// Splits "#tag" words off the description, e.g. "Fix login #bugfix" is tagged with "bugfix"
+pub fn parse_tags(input: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+    for word in input.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() => {
+                let tag = tag.to_lowercase();
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), tags)
+}
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn parse_date(input: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(input.trim(), DATE_FORMAT).ok()
+}
+
+// Dates of the entries to include, both ends are inclusive and open if not given
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateRange {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+}
+
+impl DateRange {
+    pub fn contains(&self, date: chrono::NaiveDate) -> bool {
+        self.from.iter().all(|from| date >= *from) && self.to.iter().all(|to| date <= *to)
+    }
+}
+
+pub fn format_date_time(date_time: chrono::DateTime<chrono::Utc>) -> String {
+    date_time
+        .with_timezone(&chrono::Local)
+        .format(DATE_TIME_FORMAT)
+        .to_string()
+}
+
+pub fn parse_date_time(input: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::TimeZone;
+
+    let naive = chrono::NaiveDateTime::parse_from_str(input.trim(), DATE_TIME_FORMAT).ok()?;
+    chrono::Local
+        .from_local_datetime(&naive)
+        .single()
+        .map(|date_time| date_time.with_timezone(&chrono::Utc))
+}
+
+pub fn format_duration(duration: chrono::Duration) -> String {
+    format!(
+        "{}:{:02}",
+        duration.num_hours(),
+        duration.num_minutes() % 60
+    )
+}
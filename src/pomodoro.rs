@@ -0,0 +1,131 @@
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::model::{Interval, IntervalSource};
+use crate::timer::Timer;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Phase::Work => "Work",
+                Phase::ShortBreak => "Short break",
+                Phase::LongBreak => "Long break",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PomodoroSettings {
+    pub work: chrono::Duration,
+    pub short_break: chrono::Duration,
+    pub long_break: chrono::Duration,
+    // Work cycles until a long break takes the place of a short one
+    pub cycles: u32,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        PomodoroSettings {
+            work: chrono::Duration::minutes(25),
+            short_break: chrono::Duration::minutes(5),
+            long_break: chrono::Duration::minutes(15),
+            cycles: 4,
+        }
+    }
+}
+
+// Alternates work and breaks, each phase counts down and the next one starts when it is over
+#[derive(Debug, Clone)]
+pub struct Pomodoro {
+    pub settings: PomodoroSettings,
+    pub phase: Phase,
+    pub completed_cycles: u32,
+    // Counts up the time spent in the current phase
+    timer: Timer,
+}
+
+impl Pomodoro {
+    pub fn new(settings: PomodoroSettings, clock: &dyn Clock) -> Self {
+        Pomodoro {
+            settings,
+            phase: Phase::Work,
+            completed_cycles: 0,
+            timer: Timer::paused(chrono::Duration::zero(), clock),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.timer.is_running
+    }
+
+    pub fn start_stop(&mut self, clock: &dyn Clock) {
+        self.timer.start_stop(clock);
+    }
+
+    // Starts over with the first work cycle
+    pub fn reset(&mut self, clock: &dyn Clock) {
+        *self = Pomodoro::new(self.settings.clone(), clock);
+    }
+
+    pub fn phase_length(&self) -> chrono::Duration {
+        match self.phase {
+            Phase::Work => self.settings.work,
+            Phase::ShortBreak => self.settings.short_break,
+            Phase::LongBreak => self.settings.long_break,
+        }
+    }
+
+    pub fn remaining(&self, clock: &dyn Clock) -> chrono::Duration {
+        std::cmp::max(
+            self.phase_length() - self.timer.get_current_duration(clock),
+            chrono::Duration::zero(),
+        )
+    }
+
+    // The work cycle within the current round, starting at 1
+    pub fn cycle(&self) -> u32 {
+        self.completed_cycles % self.settings.cycles.max(1) + 1
+    }
+
+    // Moves on to the next phases once the current ones are over and returns the completed work
+    // sessions. Phases that ended while nothing checked the time are caught up on as well.
+    pub fn advance(&mut self, clock: &dyn Clock) -> Vec<Interval> {
+        let mut sessions = Vec::new();
+        while self.timer.is_running
+            && self.phase_length() > chrono::Duration::zero()
+            && self.timer.get_current_duration(clock) >= self.phase_length()
+        {
+            let end = self.timer.start_time + self.phase_length();
+            self.phase = match self.phase {
+                Phase::Work => {
+                    sessions.push(Interval::new(
+                        end - self.phase_length(),
+                        end,
+                        IntervalSource::Pomodoro,
+                    ));
+                    self.completed_cycles += 1;
+                    // The last work cycle of a round is followed by the long break
+                    if self.cycle() == 1 {
+                        Phase::LongBreak
+                    } else {
+                        Phase::ShortBreak
+                    }
+                }
+                Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+            };
+            self.timer = Timer::running_since(end, clock);
+        }
+        sessions
+    }
+}
@@ -0,0 +1,69 @@
+use crate::clock::Clock;
+use crate::database::Result;
+use crate::model::{Client, Project, TrackedTime};
+use crate::timer::Timer;
+
+// Persistence used by the frontends, entries get their ID assigned when they are first saved
+pub trait Storage {
+    fn load_timers(&self, clock: &dyn Clock) -> Result<Vec<Timer>>;
+    // Makes the stored timers match the given ones, timers get their ID assigned like entries
+    fn store_timers(&self, timers: &mut [Timer]) -> Result<()>;
+
+    // Frontend settings such as the dark mode
+    fn load_setting(&self, key: &str) -> Result<Option<i64>>;
+    fn store_setting(&self, key: &str, value: i64) -> Result<()>;
+    fn load_settings(&self) -> Result<Vec<(String, i64)>>;
+
+    fn load_tracked_times(&self) -> Result<Vec<TrackedTime>>;
+    fn save_tracked_time(&self, tracked_time: &mut TrackedTime) -> Result<()>;
+    fn delete_tracked_time(&self, tracked_time: &TrackedTime) -> Result<()>;
+
+    // Writes the changes in one transaction, so time moved between entries and timers is
+    // never lost or counted twice
+    fn save_changes(&self, changes: Changes) -> Result<()>;
+
+    fn load_clients(&self) -> Result<Vec<Client>>;
+    fn load_projects(&self) -> Result<Vec<Project>>;
+    // Creates the client as well if no client with that name exists yet
+    fn insert_project(&self, name: &str, client_name: &str, color: u32) -> Result<i64>;
+    // Entries of the project are kept without a project
+    fn delete_project(&self, id: i64) -> Result<()>;
+
+    // Replaces everything that is stored at once, the IDs of the given data are kept
+    fn replace_all(&self, dataset: &mut Dataset) -> Result<()>;
+    // Adds the given data in one transaction and keeps what is stored. Clients and projects keep
+    // their IDs, timers and entries without an ID get a new one, settings that are set are kept.
+    fn insert_all(&self, dataset: &mut Dataset) -> Result<()>;
+}
+
+// Rows to store together, rows that are not listed are left as they are stored
+#[derive(Debug, Default)]
+pub struct Changes<'a> {
+    // Inserted or updated, new rows get their ID assigned
+    pub tracked_times: Vec<&'a mut TrackedTime>,
+    pub timers: Vec<&'a mut Timer>,
+    pub deleted_tracked_times: Vec<i64>,
+    pub deleted_timers: Vec<i64>,
+}
+
+// Everything that is stored, exported and imported as a whole
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub settings: Vec<(String, i64)>,
+    pub clients: Vec<Client>,
+    pub projects: Vec<Project>,
+    pub timers: Vec<Timer>,
+    pub tracked_times: Vec<TrackedTime>,
+}
+
+impl Dataset {
+    pub fn load(storage: &impl Storage, clock: &dyn Clock) -> Result<Self> {
+        Ok(Dataset {
+            settings: storage.load_settings()?,
+            clients: storage.load_clients()?,
+            projects: storage.load_projects()?,
+            timers: storage.load_timers(clock)?,
+            tracked_times: storage.load_tracked_times()?,
+        })
+    }
+}
@@ -1,6 +1,6 @@
-use iced::{button, container, rule, text_input, Color};
+use iced::{button, checkbox, container, pick_list, rule, text_input, Color};
 
-const PRIMARY: Color = Color {
+pub const PRIMARY: Color = Color {
     r: 1.00,
     g: 0.6,
     b: 0.2,
@@ -77,13 +77,21 @@ const DARK_TEXT: Color = Color {
     a: 1.0,
 };
 
-const GRAY: Color = Color {
+pub const GRAY: Color = Color {
     r: 0.6,
     g: 0.6,
     b: 0.6,
     a: 1.0,
 };
 
+// Timers and entries that ran over their time box
+pub const OVERRUN: Color = Color {
+    r: 0.82,
+    g: 0.0,
+    b: 0.38,
+    a: 1.0,
+};
+
 pub struct RootStyle {
     pub is_dark_mode: bool,
 }
@@ -121,6 +129,7 @@ impl container::StyleSheet for TextStyle {
 pub struct TimerStyle {
     pub is_dark_mode: bool,
     pub is_running: bool,
+    pub is_overrun: bool,
     pub opacity: f32,
 }
 
@@ -128,9 +137,10 @@ impl container::StyleSheet for TimerStyle {
     fn style(&self) -> container::Style {
         let o = self.opacity * if self.is_dark_mode { 0.5 } else { 1.0 };
         container::Style {
-            text_color: Some(match self.is_running {
-                true => Color::from_rgba8(0x00, 0x90, 0x40, o),
-                false => Color::from_rgba8(0xc8, 0x40, 0x00, o),
+            text_color: Some(match (self.is_overrun, self.is_running) {
+                (true, _) => Color { a: o, ..OVERRUN },
+                (false, true) => Color::from_rgba8(0x00, 0x90, 0x40, o),
+                (false, false) => Color::from_rgba8(0xc8, 0x40, 0x00, o),
             }),
             ..container::Style::default()
         }
@@ -190,6 +200,112 @@ impl text_input::StyleSheet for TextInputStyle {
     }
 }
 
+pub struct CheckboxStyle {
+    pub is_dark_mode: bool,
+}
+
+impl checkbox::StyleSheet for CheckboxStyle {
+    fn active(&self, _is_checked: bool) -> checkbox::Style {
+        checkbox::Style {
+            background: if self.is_dark_mode {
+                DARK2.into()
+            } else {
+                LIGHT.into()
+            },
+            checkmark_color: PRIMARY,
+            border_radius: 0.0,
+            border_width: 1.0,
+            border_color: DARK4,
+        }
+    }
+
+    fn hovered(&self, is_checked: bool) -> checkbox::Style {
+        checkbox::Style {
+            background: if self.is_dark_mode {
+                DARK3.into()
+            } else {
+                LIGHT2.into()
+            },
+            ..self.active(is_checked)
+        }
+    }
+}
+
+pub struct PickListStyle {
+    pub is_dark_mode: bool,
+}
+
+impl pick_list::StyleSheet for PickListStyle {
+    fn menu(&self) -> pick_list::Menu {
+        pick_list::Menu {
+            text_color: if self.is_dark_mode {
+                LIGHT_TEXT
+            } else {
+                DARK_TEXT
+            },
+            background: if self.is_dark_mode {
+                DARK2.into()
+            } else {
+                LIGHT.into()
+            },
+            border_width: 1.0,
+            border_color: if self.is_dark_mode { DARK4 } else { LIGHT4 },
+            selected_text_color: DARK_TEXT,
+            selected_background: PRIMARY.into(),
+        }
+    }
+
+    fn active(&self) -> pick_list::Style {
+        pick_list::Style {
+            text_color: if self.is_dark_mode {
+                LIGHT_TEXT
+            } else {
+                DARK_TEXT
+            },
+            background: if self.is_dark_mode {
+                DARK2.into()
+            } else {
+                LIGHT.into()
+            },
+            border_radius: 0.0,
+            border_width: if self.is_dark_mode { 0.0 } else { 1.0 },
+            border_color: DARK4,
+            ..pick_list::Style::default()
+        }
+    }
+
+    fn hovered(&self) -> pick_list::Style {
+        pick_list::Style {
+            background: if self.is_dark_mode {
+                DARK3.into()
+            } else {
+                LIGHT2.into()
+            },
+            ..self.active()
+        }
+    }
+}
+
+// Project colors are stored as 0xRRGGBB
+pub fn project_color(color: u32) -> Color {
+    Color::from_rgb8((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
+
+pub struct ProjectLabelStyle {
+    pub color: Color,
+}
+
+impl container::StyleSheet for ProjectLabelStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: LIGHT.into(),
+            background: self.color.into(),
+            border_radius: 3.0,
+            ..container::Style::default()
+        }
+    }
+}
+
 pub struct RuleStyle;
 
 impl rule::StyleSheet for RuleStyle {
@@ -250,6 +366,29 @@ impl container::StyleSheet for TooltipStyle {
     }
 }
 
+pub struct ErrorStyle;
+
+impl container::StyleSheet for ErrorStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: LIGHT.into(),
+            background: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
+            ..container::Style::default()
+        }
+    }
+}
+
+pub struct ValidationStyle;
+
+impl container::StyleSheet for ValidationStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: Color::from_rgb8(0xc8, 0x40, 0x00).into(),
+            ..container::Style::default()
+        }
+    }
+}
+
 pub struct ButtonStyle {
     pub is_dark_mode: bool,
     pub foreground: Option<Color>,
@@ -297,3 +436,16 @@ impl button::StyleSheet for ButtonStyle {
         }
     }
 }
+
+pub struct ToastStyle;
+
+impl container::StyleSheet for ToastStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: LIGHT_TEXT.into(),
+            background: DARK3.into(),
+            border_radius: 4.0,
+            ..container::Style::default()
+        }
+    }
+}
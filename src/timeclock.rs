@@ -0,0 +1,270 @@
+use crate::import::{self, LineError};
+use crate::model::{DateRange, Interval, TrackedTime};
+use crate::storage::Dataset;
+use crate::tracker::Tracker;
+
+// Ledger's timeclock format clocks in and out of an account in local time:
+// i 2021/06/01 09:00:00 Client:Project  Description  ; :tag1:tag2:
+// o 2021/06/01 10:00:00
+const TIME_FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+// Ledger needs an account, entries without a project are booked to this one
+pub const NO_PROJECT_ACCOUNT: &str = "Unassigned";
+
+// Sessions are written in the order they started, each clocked out before the next one
+pub fn export(tracker: &Tracker, range: &DateRange) -> String {
+    let mut sessions: Vec<(&Interval, &TrackedTime)> = Vec::new();
+    for tracked_time in tracker.tracked_times.iter() {
+        if range.contains(tracked_time.date) {
+            for interval in tracked_time.intervals.iter() {
+                sessions.push((interval, tracked_time));
+            }
+        }
+    }
+    sessions.sort_by_key(|(interval, _)| interval.start);
+
+    let mut timeclock = String::new();
+    for (interval, tracked_time) in sessions {
+        let project = tracked_time.project_id.and_then(|id| tracker.project(id));
+        let account = match project {
+            Some(project) => match project.client_id.and_then(|id| tracker.client(id)) {
+                Some(client) => format!(
+                    "{}:{}",
+                    account_name(&client.name),
+                    account_name(&project.name)
+                ),
+                None => account_name(&project.name),
+            },
+            None => String::from(NO_PROJECT_ACCOUNT),
+        };
+        let mut line = format!("i {} {}", format_time(interval.start), account);
+        if !tracked_time.description.is_empty() {
+            line.push_str("  ");
+            line.push_str(&tracked_time.description);
+        }
+        if !tracked_time.tags.is_empty() {
+            line.push_str(&format!("  ; :{}:", tracked_time.tags.join(":")));
+        }
+        timeclock.push_str(&line);
+        timeclock.push('\n');
+        timeclock.push_str(&format!("o {}\n", format_time(interval.end)));
+    }
+    timeclock
+}
+
+// Sessions of the same work on the same day become one entry. The last part of the account
+// is the project and the parts before it the client. A clock-in without a clock-out
+// is still running and left out.
+pub fn import(input: &str) -> Result<Dataset, LineError> {
+    let mut dataset = Dataset::default();
+    // Start, description, tags and project of the open clock-in
+    let mut clocked_in = None;
+    for (i, line) in input.lines().enumerate() {
+        let invalid = |message: &str| LineError {
+            line: i + 1,
+            message: message.to_string(),
+        };
+        let line = line.trim_end();
+        // Ledger comments start with ; # % | or *
+        if line.trim().is_empty() || line.starts_with(&[';', '#', '%', '|', '*'][..]) {
+            continue;
+        }
+        let mut parts = line.splitn(4, ' ').filter(|part| !part.is_empty());
+        let code = parts.next().unwrap_or("");
+        let time = match (parts.next(), parts.next()) {
+            (Some(date), Some(time)) => import::parse_local(date, time),
+            _ => None,
+        }
+        .ok_or_else(|| invalid("Expected a date and time like 2021/06/01 09:00:00"))?;
+        let rest = parts.next().unwrap_or("").trim();
+
+        match code {
+            "i" | "I" => {
+                if clocked_in.is_some() {
+                    return Err(invalid("Clocked in again without clocking out"));
+                }
+                let (account, rest) = split_field(rest);
+                let (description, note) = match rest.strip_prefix(';') {
+                    Some(note) => ("", note),
+                    None => match rest.find(" ;").or_else(|| rest.find("\t;")) {
+                        Some(index) => (&rest[..index], &rest[index + 2..]),
+                        None => (rest, ""),
+                    },
+                };
+                let project_id = match account.rsplitn(2, ':').collect::<Vec<&str>>()[..] {
+                    [NO_PROJECT_ACCOUNT] | [""] => None,
+                    [project] => import::find_or_add_project(&mut dataset, project, ""),
+                    [project, client] => import::find_or_add_project(&mut dataset, project, client),
+                    _ => None,
+                };
+                clocked_in = Some((
+                    time,
+                    description.trim().to_string(),
+                    parse_tags(note),
+                    project_id,
+                ));
+            }
+            "o" | "O" => match clocked_in.take() {
+                Some((start, description, tags, project_id)) => {
+                    if time < start {
+                        return Err(invalid("Clocked out before clocking in"));
+                    }
+                    import::add_imported_session(
+                        &mut dataset,
+                        description,
+                        tags,
+                        start,
+                        time,
+                        project_id,
+                    );
+                }
+                None => return Err(invalid("Clocked out without clocking in")),
+            },
+            _ => return Err(invalid("Lines start with i to clock in or o to clock out")),
+        }
+    }
+    Ok(dataset)
+}
+
+fn format_time(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.with_timezone(&chrono::Local)
+        .format(TIME_FORMAT)
+        .to_string()
+}
+
+// Two spaces or a tab end an account, colons would nest it deeper
+fn account_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .replace(':', "-")
+}
+
+// The account ends at two spaces or a tab
+fn split_field(input: &str) -> (&str, &str) {
+    match input.find("  ").into_iter().chain(input.find('\t')).min() {
+        Some(index) => (&input[..index], input[index..].trim()),
+        None => (input, ""),
+    }
+}
+
+// Ledger tags are written as :tag1:tag2: in the note
+fn parse_tags(note: &str) -> Vec<String> {
+    note.split_whitespace()
+        .filter(|word| word.len() > 1 && word.starts_with(':') && word.ends_with(':'))
+        .flat_map(|word| word.split(':'))
+        .map(import::imported_tag)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::model::{Client, IntervalSource, Project};
+    use chrono::{Duration, TimeZone};
+    use std::rc::Rc;
+
+    fn time(hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Local
+            .ymd(2021, 6, 1)
+            .and_hms(hour, minute, 0)
+            .with_timezone(&chrono::Utc)
+    }
+
+    // An entry tagged "web" in the project "Web" of the client "Acme" and one without a project
+    fn tracker() -> Tracker {
+        let mut tracker = Tracker::empty(Rc::new(ManualClock::new(time(18, 0))));
+        tracker.clients.push(Client {
+            id: 1,
+            name: String::from("Acme"),
+        });
+        tracker.projects.push(Project {
+            id: 1,
+            name: String::from("Web"),
+            client_id: Some(1),
+            color: 0x3a86ff,
+        });
+        let mut tracked_time = TrackedTime::new(
+            String::from("Fix login"),
+            vec![Interval::new(
+                time(9, 0),
+                time(10, 0),
+                IntervalSource::Timer,
+            )],
+            time(18, 0),
+        );
+        tracked_time.tags = vec![String::from("web"), String::from("bug")];
+        tracked_time.project_id = Some(1);
+        tracker.tracked_times.push(tracked_time);
+        tracker.tracked_times.push(TrackedTime::new(
+            String::from("Plan"),
+            vec![Interval::new(
+                time(10, 0),
+                time(10, 15),
+                IntervalSource::Timer,
+            )],
+            time(18, 0),
+        ));
+        tracker
+    }
+
+    #[test]
+    fn writes_sessions_in_local_time() {
+        let timeclock = export(&tracker(), &DateRange::default());
+        assert_eq!(
+            timeclock,
+            "i 2021/06/01 09:00:00 Acme:Web  Fix login  ; :web:bug:\n\
+            o 2021/06/01 10:00:00\n\
+            i 2021/06/01 10:00:00 Unassigned  Plan\n\
+            o 2021/06/01 10:15:00\n"
+        );
+    }
+
+    #[test]
+    fn reads_its_own_export() {
+        let dataset = import(&export(&tracker(), &DateRange::default())).unwrap();
+
+        assert_eq!(dataset.clients.len(), 1);
+        assert_eq!(dataset.clients[0].name, "Acme");
+        assert_eq!(dataset.projects.len(), 1);
+        assert_eq!(dataset.projects[0].name, "Web");
+        assert_eq!(dataset.tracked_times.len(), 2);
+        let tracked_time = &dataset.tracked_times[0];
+        assert_eq!(tracked_time.description, "Fix login");
+        assert_eq!(
+            tracked_time.tags,
+            vec![String::from("web"), String::from("bug")]
+        );
+        assert_eq!(tracked_time.project_id, Some(dataset.projects[0].id));
+        assert_eq!(tracked_time.intervals[0].start, time(9, 0));
+        assert_eq!(tracked_time.duration(), Duration::hours(1));
+        assert_eq!(dataset.tracked_times[1].project_id, None);
+    }
+
+    #[test]
+    fn keeps_tags_that_differ_in_case_once() {
+        let dataset = import(
+            "; a comment\n\
+            i 2021/06/01 09:00:00 Web  Review ; :a:A:\n\
+            o 2021/06/01 09:30:00\n\
+            i 2021/06/01 09:30:00 Web\n",
+        )
+        .unwrap();
+        assert_eq!(dataset.tracked_times.len(), 1);
+        assert_eq!(dataset.tracked_times[0].description, "Review");
+        assert_eq!(dataset.tracked_times[0].tags, vec![String::from("a")]);
+    }
+
+    #[test]
+    fn reports_the_line_of_unmatched_clock_codes() {
+        let error = import("o 2021/06/01 09:00:00\n");
+        assert_eq!(error.unwrap_err().line, 1);
+        let error = import("i 2021/06/01 09:00:00 Web\ni 2021/06/01 10:00:00 Web\n");
+        assert_eq!(error.unwrap_err().line, 2);
+        let error = import("i 2021/06/01 10:00:00 Web\no 2021/06/01 09:00:00\n");
+        assert_eq!(error.unwrap_err().line, 2);
+        let error = import("i yesterday Web\n");
+        assert_eq!(error.unwrap_err().line, 1);
+    }
+}
@@ -0,0 +1,390 @@
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::model::{Interval, IntervalSource};
+
+pub const DEFAULT_TIMER_NAME: &str = "Timer";
+// Far above any tracked time, keeps durations and the times calculated with them from overflowing
+pub const MAX_SECONDS: i64 = 1_000_000 * 3600;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timer {
+    // Assigned when the timer is first stored
+    pub id: Option<i64>,
+    pub name: String,
+    // Planned length, the timer counts down towards it
+    pub time_box: Option<chrono::Duration>,
+    pub is_running: bool,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+    pub pause_time: chrono::DateTime<chrono::Utc>,
+}
+
+impl Timer {
+    pub fn new(name: String, clock: &dyn Clock) -> Self {
+        let mut timer = Timer::paused(chrono::Duration::zero(), clock);
+        timer.name = name;
+        timer
+    }
+
+    pub fn paused(duration: chrono::Duration, clock: &dyn Clock) -> Self {
+        let now = clock.now();
+        Timer {
+            id: None,
+            name: String::from(DEFAULT_TIMER_NAME),
+            time_box: None,
+            is_running: false,
+            start_time: now - duration,
+            pause_time: now,
+        }
+    }
+
+    pub fn running_since(start_time: chrono::DateTime<chrono::Utc>, clock: &dyn Clock) -> Self {
+        Timer {
+            id: None,
+            name: String::from(DEFAULT_TIMER_NAME),
+            time_box: None,
+            is_running: true,
+            start_time,
+            pause_time: clock.now(),
+        }
+    }
+
+    pub fn get_current_duration(&self, clock: &dyn Clock) -> chrono::Duration {
+        match self.is_running {
+            true => clock.now() - self.start_time,
+            false => self.pause_time - self.start_time,
+        }
+    }
+
+    // Time left in the time box, negative once the timer ran over
+    pub fn remaining(&self, clock: &dyn Clock) -> Option<chrono::Duration> {
+        self.time_box
+            .map(|time_box| time_box - self.get_current_duration(clock))
+    }
+
+    pub fn is_overrun(&self, clock: &dyn Clock) -> bool {
+        match self.remaining(clock) {
+            Some(remaining) => remaining < chrono::Duration::zero(),
+            None => false,
+        }
+    }
+
+    pub fn start_stop(&mut self, clock: &dyn Clock) {
+        if self.is_running {
+            self.pause_time = clock.now();
+        } else {
+            self.start_time = self.start_time + (clock.now() - self.pause_time);
+        }
+        self.is_running = !self.is_running;
+    }
+
+    pub fn clear(&mut self, clock: &dyn Clock) {
+        self.start_time = clock.now();
+        self.pause_time = self.start_time;
+    }
+
+    // Time returned from an entry continues the timer
+    pub(crate) fn give(&mut self, duration: chrono::Duration) {
+        self.start_time = self.start_time - duration;
+    }
+
+    // Time split off the timer is logged as the session at the start of the timer, so repeated
+    // splits follow each other instead of overlapping
+    pub(crate) fn take(&mut self, duration: chrono::Duration) -> Interval {
+        let start = self.start_time;
+        self.start_time = self.start_time + duration;
+        Interval::new(start, start + duration, IntervalSource::Timer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DurationError {
+    Invalid(String),
+    Negative,
+    // The time that is available, the timer or the entry the time is taken from
+    ExceedsAvailable(chrono::Duration),
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DurationError::Invalid(message) => write!(f, "{}", message),
+            DurationError::Negative => write!(f, "The duration is negative"),
+            DurationError::ExceedsAvailable(available) => write!(
+                f,
+                "The duration exceeds the available {}:{:02}:{:02}",
+                available.num_hours(),
+                available.num_minutes() % 60,
+                available.num_seconds() % 60
+            ),
+        }
+    }
+}
+
+// Accepted forms, an empty input is all of the available time:
+// "90" (minutes), "1:30", "1:30:15", "1h30m", "1.5h", "45s", "50%" of it and "all-10m"
+pub fn parse_duration(
+    input: &str,
+    available: chrono::Duration,
+) -> Result<chrono::Duration, DurationError> {
+    let input = input.trim().to_lowercase();
+    let duration = if input.is_empty() || input == "all" {
+        available
+    } else if let Some(rest) = input.strip_prefix("all") {
+        match rest.trim_start().strip_prefix('-') {
+            Some(amount) => available - parse_amount(amount.trim())?,
+            None => return Err(invalid(&input)),
+        }
+    } else if let Some(percent) = input.strip_suffix('%') {
+        let percent = parse_number(percent.trim()).ok_or_else(|| invalid(&input))?;
+        if percent > 100.0 {
+            return Err(DurationError::Invalid(String::from(
+                "A percentage can be at most 100%",
+            )));
+        }
+        chrono::Duration::seconds((available.num_seconds() as f64 * percent / 100.0).round() as i64)
+    } else {
+        parse_amount(&input)?
+    };
+
+    if duration < chrono::Duration::zero() {
+        return Err(DurationError::Negative);
+    }
+    if duration > available {
+        return Err(DurationError::ExceedsAvailable(available));
+    }
+    Ok(duration)
+}
+
+// A duration on its own, without a time it is a part of
+pub fn parse_length(input: &str) -> Result<chrono::Duration, DurationError> {
+    let input = input.trim().to_lowercase();
+    let duration = parse_amount(&input)?;
+    if duration < chrono::Duration::zero() {
+        return Err(DurationError::Negative);
+    }
+    Ok(duration)
+}
+
+fn parse_amount(input: &str) -> Result<chrono::Duration, DurationError> {
+    if input.is_empty() {
+        return Err(invalid(input));
+    }
+    if input.contains(':') {
+        return parse_clock(input);
+    }
+    if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+        let minutes = input.parse::<i64>().map_err(|_| invalid(input))?;
+        return seconds(minutes.checked_mul(60), input);
+    }
+
+    // Numbers each followed by a unit, e.g. "1h30m"
+    let mut seconds = 0.0;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .ok_or_else(|| invalid(input))?;
+        let number = parse_number(&rest[..number_end]).ok_or_else(|| invalid(input))?;
+        rest = rest[number_end..].trim_start();
+
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        seconds += number
+            * match &rest[..unit_end] {
+                "h" => 3600.0,
+                "m" | "min" => 60.0,
+                "s" => 1.0,
+                _ => return Err(invalid(input)),
+            };
+        rest = rest[unit_end..].trim_start();
+    }
+    // Checked before converting, too large floats would saturate
+    if !seconds.is_finite() || seconds.round() > MAX_SECONDS as f64 {
+        return Err(too_long(input));
+    }
+    Ok(chrono::Duration::seconds(seconds.round() as i64))
+}
+
+// "H:MM" or "H:MM:SS"
+fn parse_clock(input: &str) -> Result<chrono::Duration, DurationError> {
+    // Only digits, a sign would be read into the part instead of the whole duration
+    let parts = input
+        .split(':')
+        .map(|part| part.trim())
+        .map(|part| {
+            if part.chars().all(|c| c.is_ascii_digit()) {
+                part.parse::<i64>().ok()
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<i64>>>()
+        .ok_or_else(|| invalid(input))?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes] => (*hours, *minutes, 0),
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        _ => return Err(invalid(input)),
+    };
+    if minutes >= 60 || seconds >= 60 {
+        return Err(DurationError::Invalid(format!(
+            "Minutes and seconds in '{}' must be below 60",
+            input
+        )));
+    }
+    let total = hours
+        .checked_mul(3600)
+        .and_then(|total| total.checked_add(minutes * 60 + seconds));
+    self::seconds(total, input)
+}
+
+// None if calculating the seconds overflowed
+fn seconds(seconds: Option<i64>, input: &str) -> Result<chrono::Duration, DurationError> {
+    match seconds {
+        Some(seconds) if (-MAX_SECONDS..=MAX_SECONDS).contains(&seconds) => {
+            Ok(chrono::Duration::seconds(seconds))
+        }
+        _ => Err(too_long(input)),
+    }
+}
+
+fn parse_number(input: &str) -> Option<f64> {
+    if input.is_empty() || !input.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    input.parse::<f64>().ok()
+}
+
+fn too_long(input: &str) -> DurationError {
+    DurationError::Invalid(format!("'{}' is too long for a duration", input))
+}
+
+fn invalid(input: &str) -> DurationError {
+    DurationError::Invalid(format!(
+        "'{}' is not a duration, use e.g. 90, 1:30, 1h30m, 1.5h, 45s, 50% or all-10m",
+        input
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use chrono::{Duration, TimeZone};
+
+    fn clock() -> ManualClock {
+        ManualClock::new(chrono::Utc.ymd(2021, 3, 1).and_hms(9, 0, 0))
+    }
+
+    #[test]
+    fn counts_only_while_running() {
+        let clock = clock();
+        let mut timer = Timer::new(String::from("Work"), &clock);
+        clock.advance(Duration::minutes(5));
+        assert_eq!(timer.get_current_duration(&clock), Duration::zero());
+
+        timer.start_stop(&clock);
+        clock.advance(Duration::minutes(10));
+        assert_eq!(timer.get_current_duration(&clock), Duration::minutes(10));
+
+        timer.start_stop(&clock);
+        clock.advance(Duration::minutes(20));
+        assert!(!timer.is_running);
+        assert_eq!(timer.get_current_duration(&clock), Duration::minutes(10));
+
+        timer.start_stop(&clock);
+        clock.advance(Duration::minutes(2));
+        assert_eq!(timer.get_current_duration(&clock), Duration::minutes(12));
+    }
+
+    #[test]
+    fn clear_keeps_a_running_timer_running() {
+        let clock = clock();
+        let mut timer = Timer::new(String::from("Work"), &clock);
+        timer.start_stop(&clock);
+        clock.advance(Duration::minutes(30));
+
+        timer.clear(&clock);
+        assert_eq!(timer.get_current_duration(&clock), Duration::zero());
+        clock.advance(Duration::minutes(1));
+        assert!(timer.is_running);
+        assert_eq!(timer.get_current_duration(&clock), Duration::minutes(1));
+    }
+
+    #[test]
+    fn continues_from_stored_state() {
+        let clock = clock();
+        let start = clock.now() - Duration::hours(2);
+        let running = Timer::running_since(start, &clock);
+        assert_eq!(running.get_current_duration(&clock), Duration::hours(2));
+
+        let mut paused = Timer::paused(Duration::minutes(45), &clock);
+        clock.advance(Duration::hours(1));
+        assert_eq!(paused.get_current_duration(&clock), Duration::minutes(45));
+        paused.start_stop(&clock);
+        clock.advance(Duration::minutes(15));
+        assert_eq!(paused.get_current_duration(&clock), Duration::hours(1));
+    }
+
+    #[test]
+    fn taken_time_follows_on_from_earlier_splits() {
+        let clock = clock();
+        let started = clock.now();
+        let mut timer = Timer::new(String::from("Work"), &clock);
+        timer.start_stop(&clock);
+        clock.advance(Duration::hours(1));
+
+        let first = timer.take(Duration::minutes(30));
+        let second = timer.take(Duration::minutes(20));
+        assert_eq!(first.start, started);
+        assert_eq!(first.end, second.start);
+        assert_eq!(second.end, started + Duration::minutes(50));
+        assert_eq!(timer.get_current_duration(&clock), Duration::minutes(10));
+
+        timer.give(Duration::minutes(5));
+        assert_eq!(timer.get_current_duration(&clock), Duration::minutes(15));
+    }
+
+    #[test]
+    fn time_box_counts_down() {
+        let clock = clock();
+        let mut timer = Timer::new(String::from("Work"), &clock);
+        timer.time_box = Some(Duration::minutes(25));
+        timer.start_stop(&clock);
+        clock.advance(Duration::minutes(20));
+        assert_eq!(timer.remaining(&clock), Some(Duration::minutes(5)));
+        assert!(!timer.is_overrun(&clock));
+        clock.advance(Duration::minutes(6));
+        assert!(timer.is_overrun(&clock));
+    }
+
+    #[test]
+    fn parses_parts_of_the_available_time() {
+        let available = Duration::hours(2);
+        let parse = |input| parse_duration(input, available);
+        assert_eq!(parse("90"), Ok(Duration::minutes(90)));
+        assert_eq!(parse("1:30"), Ok(Duration::minutes(90)));
+        assert_eq!(parse("1.5h"), Ok(Duration::minutes(90)));
+        assert_eq!(parse("50%"), Ok(Duration::hours(1)));
+        assert_eq!(parse("all-10m"), Ok(Duration::minutes(110)));
+        assert_eq!(parse(""), Ok(available));
+        assert_eq!(parse("3h"), Err(DurationError::ExceedsAvailable(available)));
+        assert!(matches!(
+            parse("99999999999999999"),
+            Err(DurationError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_signs_in_parts_of_a_clock_time() {
+        for input in &["1:-30", "-0:30", "+1:00", "1:30:-5", "1::30"] {
+            assert!(
+                matches!(parse_length(input), Err(DurationError::Invalid(_))),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(parse_length(" 1 : 30 "), Ok(Duration::minutes(90)));
+    }
+}
@@ -0,0 +1,262 @@
+use crate::import::{self, LineError};
+use crate::model::{DateRange, Interval, TrackedTime};
+use crate::storage::Dataset;
+use crate::tracker::Tracker;
+
+// Timewarrior keeps one line per interval in UTC, as in its data files:
+// inc 20210601T090000Z - 20210601T100000Z # tag "tag two" # "annotation"
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// Sessions are written in the order they started. The description is the annotation and
+// the project is added as a tag, Timewarrior has no projects.
+pub fn export(tracker: &Tracker, range: &DateRange) -> String {
+    let mut sessions: Vec<(&Interval, &TrackedTime)> = Vec::new();
+    for tracked_time in tracker.tracked_times.iter() {
+        if range.contains(tracked_time.date) {
+            for interval in tracked_time.intervals.iter() {
+                sessions.push((interval, tracked_time));
+            }
+        }
+    }
+    sessions.sort_by_key(|(interval, _)| interval.start);
+
+    let mut data = String::new();
+    for (interval, tracked_time) in sessions {
+        let mut tags = tracked_time.tags.clone();
+        if let Some(project) = tracked_time.project_id.and_then(|id| tracker.project(id)) {
+            tags.push(project.name.clone());
+        }
+        data.push_str(&format!(
+            "inc {} - {}",
+            interval.start.format(TIME_FORMAT),
+            interval.end.format(TIME_FORMAT)
+        ));
+        if !tags.is_empty() || !tracked_time.description.is_empty() {
+            data.push_str(" #");
+            for tag in tags.iter() {
+                data.push(' ');
+                data.push_str(&quote_tag(tag));
+            }
+        }
+        if !tracked_time.description.is_empty() {
+            data.push_str(" # ");
+            data.push_str(&quote(&tracked_time.description));
+        }
+        data.push('\n');
+    }
+    data
+}
+
+// Reads the data files Timewarrior keeps in ~/.timewarrior/data. Intervals of the same work on
+// the same day become one entry. Without an annotation the first tag is the description.
+// Open intervals are still being tracked and are left out.
+pub fn import(input: &str) -> Result<Dataset, LineError> {
+    let mut dataset = Dataset::default();
+    for (i, line) in input.lines().enumerate() {
+        let invalid = |message: &str| LineError {
+            line: i + 1,
+            message: message.to_string(),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let tokens = match line.strip_prefix("inc ") {
+            Some(rest) => tokenize(rest).ok_or_else(|| invalid("A quote is never closed"))?,
+            None => return Err(invalid("Intervals start with 'inc'")),
+        };
+        let mut tokens = tokens.into_iter().peekable();
+
+        let start = tokens
+            .next()
+            .and_then(|(token, _)| parse_time(&token))
+            .ok_or_else(|| invalid("The start is not a time like 20210601T090000Z"))?;
+        let end = match tokens.peek() {
+            Some((token, false)) if token == "-" => {
+                tokens.next();
+                tokens
+                    .next()
+                    .and_then(|(token, _)| parse_time(&token))
+                    .ok_or_else(|| invalid("The end is not a time like 20210601T100000Z"))?
+            }
+            _ => continue,
+        };
+        if end < start {
+            return Err(invalid("The interval ends before it starts"));
+        }
+
+        let mut tags = Vec::new();
+        let mut annotation = Vec::new();
+        let mut separators = 0;
+        for (token, is_quoted) in tokens {
+            match (token.as_str(), is_quoted, separators) {
+                ("#", false, 0) | ("#", false, 1) => separators += 1,
+                (_, _, 0) => return Err(invalid("Tags have to follow a '#'")),
+                (_, _, 1) => tags.push(token),
+                _ => annotation.push(token),
+            }
+        }
+        let description = if annotation.is_empty() && !tags.is_empty() {
+            tags.remove(0)
+        } else {
+            annotation.join(" ")
+        };
+        let tags = tags
+            .iter()
+            .map(|tag| import::imported_tag(tag))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        import::add_imported_session(&mut dataset, description, tags, start, end, None);
+    }
+    Ok(dataset)
+}
+
+fn parse_time(input: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::TimeZone;
+
+    chrono::NaiveDateTime::parse_from_str(input, TIME_FORMAT)
+        .ok()
+        .map(|naive| chrono::Utc.from_utc_datetime(&naive))
+}
+
+// Splits at whitespace outside of quotes, quoted tokens are marked so a quoted "#" is a tag.
+// None if a quote is not closed.
+fn tokenize(input: &str) -> Option<Vec<(String, bool)>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c == '"' {
+            let mut token = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => token.push(chars.next()?),
+                    c => token.push(c),
+                }
+            }
+            tokens.push((token, true));
+        } else {
+            let mut token = c.to_string();
+            while let Some(c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                token.push(*c);
+                chars.next();
+            }
+            tokens.push((token, false));
+        }
+    }
+    Some(tokens)
+}
+
+// Tags are only quoted when they would be split up or read as a separator
+fn quote_tag(tag: &str) -> String {
+    if tag.is_empty() || tag == "#" || tag.contains(|c: char| c.is_whitespace() || c == '"') {
+        quote(tag)
+    } else {
+        tag.to_string()
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::model::{IntervalSource, Project};
+    use chrono::{Duration, TimeZone};
+    use std::rc::Rc;
+
+    fn time(hour: u32, minute: u32) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.ymd(2021, 6, 1).and_hms(hour, minute, 0)
+    }
+
+    // An entry tagged "web" in the project "Web" and one with quotes in its description
+    fn tracker() -> Tracker {
+        let mut tracker = Tracker::empty(Rc::new(ManualClock::new(time(18, 0))));
+        tracker.projects.push(Project {
+            id: 1,
+            name: String::from("Web"),
+            client_id: None,
+            color: 0x3a86ff,
+        });
+        let mut tracked_time = TrackedTime::new(
+            String::from("Fix login"),
+            vec![
+                Interval::new(time(9, 0), time(10, 0), IntervalSource::Timer),
+                Interval::new(time(11, 0), time(11, 30), IntervalSource::Manual),
+            ],
+            time(18, 0),
+        );
+        tracked_time.tags = vec![String::from("web")];
+        tracked_time.project_id = Some(1);
+        tracker.tracked_times.push(tracked_time);
+        tracker.tracked_times.push(TrackedTime::new(
+            String::from("Say \"hi\""),
+            vec![Interval::new(
+                time(10, 0),
+                time(10, 15),
+                IntervalSource::Timer,
+            )],
+            time(18, 0),
+        ));
+        tracker
+    }
+
+    #[test]
+    fn writes_sessions_in_order() {
+        let data = export(&tracker(), &DateRange::default());
+        assert_eq!(
+            data,
+            "inc 20210601T090000Z - 20210601T100000Z # web Web # \"Fix login\"\n\
+            inc 20210601T100000Z - 20210601T101500Z # # \"Say \\\"hi\\\"\"\n\
+            inc 20210601T110000Z - 20210601T113000Z # web Web # \"Fix login\"\n"
+        );
+    }
+
+    #[test]
+    fn reads_its_own_export() {
+        let dataset = import(&export(&tracker(), &DateRange::default())).unwrap();
+
+        assert_eq!(dataset.tracked_times.len(), 2);
+        let tracked_time = &dataset.tracked_times[0];
+        assert_eq!(tracked_time.description, "Fix login");
+        // The project came back as a tag that is the same as the entry's tag
+        assert_eq!(tracked_time.tags, vec![String::from("web")]);
+        assert_eq!(tracked_time.intervals.len(), 2);
+        assert_eq!(tracked_time.intervals[1].start, time(11, 0));
+        assert_eq!(tracked_time.duration(), Duration::minutes(90));
+        assert_eq!(dataset.tracked_times[1].description, "Say \"hi\"");
+    }
+
+    #[test]
+    fn reads_tags_and_annotations() {
+        let dataset = import(
+            "inc 20210601T090000Z - 20210601T093000Z # review \"tag two\"\n\
+            \n\
+            inc 20210601T100000Z - 20210601T110000Z # Bug bug # Fix the build\n\
+            inc 20210601T120000Z\n",
+        )
+        .unwrap();
+
+        assert_eq!(dataset.tracked_times.len(), 2);
+        assert_eq!(dataset.tracked_times[0].description, "review");
+        assert_eq!(dataset.tracked_times[0].tags, vec![String::from("tag-two")]);
+        assert_eq!(dataset.tracked_times[1].description, "Fix the build");
+        assert_eq!(dataset.tracked_times[1].tags, vec![String::from("bug")]);
+    }
+
+    #[test]
+    fn reports_the_line_of_invalid_intervals() {
+        let error = import("inc 20210601T090000Z - 20210601T100000Z\nexc 20210601T100000Z\n");
+        assert_eq!(error.unwrap_err().line, 2);
+        let error = import("inc 20210601T100000Z - 20210601T090000Z\n");
+        assert_eq!(error.unwrap_err().line, 1);
+        let error = import("inc 20210601T090000Z - 20210601T100000Z # \"open\n");
+        assert_eq!(error.unwrap_err().line, 1);
+    }
+}
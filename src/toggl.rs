@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::csv;
+use crate::import;
+use crate::storage::Dataset;
+use crate::timer;
+
+#[derive(Debug)]
+pub enum TogglError {
+    Csv(csv::ParseError),
+    MissingColumn(&'static str),
+    // Row of the file starting at 1 for the header and what is wrong with it
+    InvalidRow(usize, String),
+}
+
+impl fmt::Display for TogglError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TogglError::Csv(error) => write!(f, "{}", error),
+            TogglError::MissingColumn(name) => write!(
+                f,
+                "The file has no '{}' column, export a detailed report from Toggl Track",
+                name
+            ),
+            TogglError::InvalidRow(row, message) => write!(f, "Row {}: {}", row, message),
+        }
+    }
+}
+
+// Reads the CSV of a detailed report, each time entry becomes an entry with one session.
+// Start and end are in local time like in the report.
+pub fn import(input: &str) -> Result<Dataset, TogglError> {
+    let mut records = csv::parse(input).map_err(TogglError::Csv)?.into_iter();
+    let header = match records.next() {
+        Some(header) => header,
+        None => return Ok(Dataset::default()),
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let required = |name: &'static str| column(name).ok_or(TogglError::MissingColumn(name));
+    let description = required("Description")?;
+    let start_date = required("Start date")?;
+    let start_time = required("Start time")?;
+    let end_date = column("End date");
+    let end_time = column("End time");
+    let duration = column("Duration");
+    let project = column("Project");
+    let client = column("Client");
+    let tags = column("Tags");
+
+    let mut dataset = Dataset::default();
+    for (i, record) in records.enumerate() {
+        let row = i + 2;
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(|field| field.trim())
+                .unwrap_or("")
+        };
+        let invalid = |message: String| TogglError::InvalidRow(row, message);
+
+        let start = import::parse_local(field(Some(start_date)), field(Some(start_time)))
+            .ok_or_else(|| invalid(String::from("The start is not a valid date and time")))?;
+        let end = if !field(end_date).is_empty() && !field(end_time).is_empty() {
+            import::parse_local(field(end_date), field(end_time))
+                .ok_or_else(|| invalid(String::from("The end is not a valid date and time")))?
+        } else {
+            start
+                + timer::parse_length(field(duration))
+                    .map_err(|error| invalid(error.to_string()))?
+        };
+        if end < start {
+            return Err(invalid(String::from("The entry ends before it starts")));
+        }
+
+        let project_id = import::find_or_add_project(&mut dataset, field(project), field(client));
+        let tags = field(tags)
+            .split(',')
+            .map(import::imported_tag)
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        dataset.tracked_times.push(import::imported_entry(
+            field(Some(description)).to_string(),
+            tags,
+            start,
+            end,
+            project_id,
+        ));
+    }
+    Ok(dataset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::IntervalSource;
+    use chrono::Duration;
+
+    const HEADER: &str = "User,Email,Client,Project,Task,Description,Billable,Start date,\
+        Start time,End date,End time,Duration,Tags,Amount ()";
+
+    fn report(rows: &[&str]) -> String {
+        std::iter::once(HEADER)
+            .chain(rows.iter().copied())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn reads_entries_with_projects_and_tags() {
+        let dataset = import(&report(&[
+            "Ann,ann@example.com,Acme,Website,,Fix login,No,2021-06-01,09:00:00,\
+                2021-06-01,10:30:00,01:30:00,\"Bug, Web dev\",",
+            "Ann,ann@example.com,,,,Plan,No,2021/06/02,14:00,2021/06/02,14:15,00:15:00,,",
+        ]))
+        .unwrap();
+
+        assert_eq!(dataset.clients.len(), 1);
+        assert_eq!(dataset.clients[0].name, "Acme");
+        assert_eq!(dataset.projects.len(), 1);
+        assert_eq!(dataset.projects[0].name, "Website");
+        assert_eq!(dataset.projects[0].client_id, Some(dataset.clients[0].id));
+
+        let tracked_time = &dataset.tracked_times[0];
+        assert_eq!(tracked_time.description, "Fix login");
+        assert_eq!(
+            tracked_time.tags,
+            vec![String::from("bug"), String::from("web-dev")]
+        );
+        assert_eq!(tracked_time.project_id, Some(dataset.projects[0].id));
+        assert_eq!(
+            tracked_time.intervals[0].start,
+            import::parse_local("2021-06-01", "09:00:00").unwrap()
+        );
+        assert_eq!(tracked_time.intervals[0].source, IntervalSource::Imported);
+        assert_eq!(tracked_time.duration(), Duration::minutes(90));
+
+        let tracked_time = &dataset.tracked_times[1];
+        assert_eq!(tracked_time.project_id, None);
+        assert!(tracked_time.tags.is_empty());
+        assert_eq!(tracked_time.duration(), Duration::minutes(15));
+    }
+
+    #[test]
+    fn keeps_tags_that_differ_in_case_once() {
+        let dataset = import(&report(&[
+            "Ann,ann@example.com,,,,Triage,No,2021-06-01,09:00:00,2021-06-01,09:30:00,\
+                00:30:00,\"Bug, bug\",",
+        ]))
+        .unwrap();
+        assert_eq!(dataset.tracked_times[0].tags, vec![String::from("bug")]);
+    }
+
+    #[test]
+    fn falls_back_to_the_duration_without_an_end() {
+        let dataset = import(
+            "Description,Start date,Start time,Duration\nReview,2021-06-01,09:00:00,00:45:00",
+        )
+        .unwrap();
+        assert_eq!(dataset.tracked_times[0].duration(), Duration::minutes(45));
+    }
+
+    #[test]
+    fn rejects_reports_it_cannot_read() {
+        assert!(matches!(
+            import("Description,Start date\nReview,2021-06-01"),
+            Err(TogglError::MissingColumn("Start time"))
+        ));
+        assert!(matches!(
+            import(&report(&[
+                "Ann,ann@example.com,,,,Review,No,yesterday,09:00:00,,,00:45:00,,"
+            ])),
+            Err(TogglError::InvalidRow(2, _))
+        ));
+        assert!(matches!(
+            import(&report(&[
+                "Ann,ann@example.com,,,,Review,No,2021-06-01,10:00:00,\
+                    2021-06-01,09:00:00,,,"
+            ])),
+            Err(TogglError::InvalidRow(2, _))
+        ));
+        assert!(import("").unwrap().tracked_times.is_empty());
+    }
+}
@@ -0,0 +1,761 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::clock::Clock;
+use crate::database::Result;
+use crate::model::{parse_tags, Client, Interval, IntervalSource, Project, TrackedTime};
+use crate::storage::{Changes, Storage};
+use crate::timer::{self, DurationError, Timer, DEFAULT_TIMER_NAME};
+
+// Everything the frontends show and edit, without any GUI state
+#[derive(Debug, Clone)]
+pub struct Tracker {
+    pub clock: Rc<dyn Clock>,
+    // There is always at least one timer
+    pub timers: Vec<Timer>,
+    pub tracked_times: Vec<TrackedTime>,
+    pub clients: Vec<Client>,
+    pub projects: Vec<Project>,
+}
+
+// Where the time split off the timer goes
+pub enum Target {
+    New {
+        description: String,
+        project_id: Option<i64>,
+    },
+    // Index starting at 1 as shown in the list
+    Existing(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperationError {
+    Duration(DurationError),
+    NoSuchEntry(usize),
+    NoSuchId(i64),
+    NoMatchingEntry(String),
+    // The numbers of all entries that match
+    AmbiguousEntry(String, Vec<usize>),
+    SameEntry,
+    EmptyDescription,
+    NoSuchTimer(String),
+    DuplicateTimer(String),
+    EmptyTimerName,
+    LastTimer,
+    EndBeforeStart,
+}
+
+impl From<DurationError> for OperationError {
+    fn from(error: DurationError) -> Self {
+        OperationError::Duration(error)
+    }
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OperationError::Duration(error) => write!(f, "{}", error),
+            OperationError::NoSuchEntry(index) => write!(f, "There is no entry {}", index),
+            OperationError::NoSuchId(id) => write!(f, "There is no entry with ID @{}", id),
+            OperationError::NoMatchingEntry(query) => {
+                write!(f, "No entry matches '{}'", query)
+            }
+            OperationError::AmbiguousEntry(query, numbers) => write!(
+                f,
+                "'{}' matches more than one entry ({}), use a number or ID",
+                query,
+                numbers
+                    .iter()
+                    .map(|number| number.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            OperationError::SameEntry => write!(f, "Time can only be moved to another entry"),
+            OperationError::EmptyDescription => write!(f, "The description cannot be empty"),
+            OperationError::NoSuchTimer(name) => write!(f, "There is no timer called '{}'", name),
+            OperationError::DuplicateTimer(name) => {
+                write!(f, "There already is a timer called '{}'", name)
+            }
+            OperationError::EmptyTimerName => write!(f, "The timer name cannot be empty"),
+            OperationError::LastTimer => write!(f, "The last timer cannot be removed"),
+            OperationError::EndBeforeStart => write!(f, "A session cannot end before it starts"),
+        }
+    }
+}
+
+impl Tracker {
+    pub fn empty(clock: Rc<dyn Clock>) -> Self {
+        Tracker {
+            timers: vec![Timer::new(String::from(DEFAULT_TIMER_NAME), clock.as_ref())],
+            clock,
+            tracked_times: Vec::new(),
+            clients: Vec::new(),
+            projects: Vec::new(),
+        }
+    }
+
+    pub fn load(storage: &impl Storage, clock: Rc<dyn Clock>) -> Result<Self> {
+        let mut timers = storage.load_timers(clock.as_ref())?;
+        if timers.is_empty() {
+            timers.push(Timer::new(String::from(DEFAULT_TIMER_NAME), clock.as_ref()));
+        }
+        let mut tracker = Tracker {
+            timers,
+            clock,
+            tracked_times: storage.load_tracked_times()?,
+            clients: Vec::new(),
+            projects: Vec::new(),
+        };
+        tracker.load_projects(storage)?;
+        Ok(tracker)
+    }
+
+    pub fn load_projects(&mut self, storage: &impl Storage) -> Result<()> {
+        self.clients = storage.load_clients()?;
+        self.projects = storage.load_projects()?;
+        Ok(())
+    }
+
+    // Timers are given by their index in the list of timers
+    pub fn current_duration(&self, timer: usize) -> chrono::Duration {
+        self.timers[timer].get_current_duration(self.clock.as_ref())
+    }
+
+    pub fn start_stop(&mut self, timer: usize) {
+        self.timers[timer].start_stop(self.clock.as_ref());
+    }
+
+    pub fn clear(&mut self, timer: usize) {
+        self.timers[timer].clear(self.clock.as_ref());
+    }
+
+    // An empty input removes the time box
+    pub fn set_time_box(
+        &mut self,
+        timer: usize,
+        input: &str,
+    ) -> std::result::Result<(), OperationError> {
+        self.timers[timer].time_box = match input.trim() {
+            "" | "off" => None,
+            input => Some(timer::parse_length(input)?),
+        };
+        Ok(())
+    }
+
+    pub fn is_any_timer_running(&self) -> bool {
+        self.timers.iter().any(|timer| timer.is_running)
+    }
+
+    // Adds a paused timer and returns its index
+    pub fn add_timer(&mut self, name: &str) -> std::result::Result<usize, OperationError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(OperationError::EmptyTimerName);
+        }
+        if self.find_timer(name).is_ok() {
+            return Err(OperationError::DuplicateTimer(name.to_string()));
+        }
+        self.timers
+            .push(Timer::new(name.to_string(), self.clock.as_ref()));
+        Ok(self.timers.len() - 1)
+    }
+
+    // The time on the removed timer is discarded
+    pub fn remove_timer(&mut self, timer: usize) -> std::result::Result<Timer, OperationError> {
+        if self.timers.len() == 1 {
+            return Err(OperationError::LastTimer);
+        }
+        Ok(self.timers.remove(timer))
+    }
+
+    // Timer names are compared ignoring case
+    pub fn find_timer(&self, name: &str) -> std::result::Result<usize, OperationError> {
+        let name = name.trim();
+        self.timers
+            .iter()
+            .position(|timer| timer.name.to_lowercase() == name.to_lowercase())
+            .ok_or_else(|| OperationError::NoSuchTimer(name.to_string()))
+    }
+
+    // Moves time from a timer into a new or an existing entry and returns the entry's index
+    pub fn apply_operation(
+        &mut self,
+        timer: usize,
+        time_input: &str,
+        target: Target,
+    ) -> std::result::Result<usize, OperationError> {
+        let available = self.current_duration(timer);
+        let duration = timer::parse_duration(time_input, available)?;
+        let now = self.clock.now();
+        // The time box goes along when all of the time is taken off the timer
+        let takes_time_box = duration == available;
+
+        let index = match target {
+            Target::New {
+                description,
+                project_id,
+            } => {
+                let (description, tags) = parse_tags(&description);
+                if description.is_empty() {
+                    return Err(OperationError::EmptyDescription);
+                }
+                let interval = self.timers[timer].take(duration);
+                let mut tracked_time = TrackedTime::new(description, vec![interval], now);
+                tracked_time.project_id = project_id;
+                tracked_time.tags = tags;
+                if takes_time_box {
+                    tracked_time.planned = self.timers[timer].time_box.take();
+                }
+                self.tracked_times.push(tracked_time);
+                self.tracked_times.len() - 1
+            }
+            Target::Existing(index) => {
+                let index = self.entry_index(index)?;
+                let tracked_time = &mut self.tracked_times[index];
+                // Time the entry had without a plan counts as planned
+                if let Some(time_box) = self.timers[timer].time_box.filter(|_| takes_time_box) {
+                    tracked_time.planned = Some(
+                        tracked_time
+                            .planned
+                            .unwrap_or_else(|| tracked_time.duration())
+                            + time_box,
+                    );
+                    self.timers[timer].time_box = None;
+                }
+                tracked_time
+                    .intervals
+                    .push(self.timers[timer].take(duration));
+                tracked_time.modified_at = now;
+                index
+            }
+        };
+        Ok(index)
+    }
+
+    // Logs sessions that were not taken off a timer as a new entry and returns its index
+    pub fn add_entry(
+        &mut self,
+        description: &str,
+        project_id: Option<i64>,
+        intervals: Vec<Interval>,
+    ) -> std::result::Result<usize, OperationError> {
+        let (description, tags) = parse_tags(description);
+        if description.is_empty() {
+            return Err(OperationError::EmptyDescription);
+        }
+        let mut tracked_time = TrackedTime::new(description, intervals, self.clock.now());
+        tracked_time.project_id = project_id;
+        tracked_time.tags = tags;
+        self.tracked_times.push(tracked_time);
+        Ok(self.tracked_times.len() - 1)
+    }
+
+    // Removes time from an entry, "all" is the whole entry
+    pub fn subtract(
+        &mut self,
+        index: usize,
+        time_input: &str,
+    ) -> std::result::Result<usize, OperationError> {
+        let index = self.entry_index(index)?;
+        self.take_from_entry(index, time_input)?;
+        Ok(index)
+    }
+
+    // Moves time from one entry to another and returns the indices of both
+    pub fn transfer(
+        &mut self,
+        from: usize,
+        to: usize,
+        time_input: &str,
+    ) -> std::result::Result<(usize, usize), OperationError> {
+        let from = self.entry_index(from)?;
+        let to = self.entry_index(to)?;
+        if from == to {
+            return Err(OperationError::SameEntry);
+        }
+        let mut intervals = self.take_from_entry(from, time_input)?;
+
+        let tracked_time = &mut self.tracked_times[to];
+        tracked_time.intervals.append(&mut intervals);
+        tracked_time
+            .intervals
+            .sort_by_key(|interval| interval.start);
+        tracked_time.modified_at = self.clock.now();
+        Ok((from, to))
+    }
+
+    // Removes time from an entry and adds it back onto a timer
+    pub fn return_to_timer(
+        &mut self,
+        index: usize,
+        timer: usize,
+        time_input: &str,
+    ) -> std::result::Result<usize, OperationError> {
+        let index = self.entry_index(index)?;
+        let intervals = self.take_from_entry(index, time_input)?;
+        let duration = intervals
+            .iter()
+            .fold(chrono::Duration::zero(), |total, interval| {
+                total + interval.duration()
+            });
+        self.timers[timer].give(duration);
+        Ok(index)
+    }
+
+    // Replaces description and tags, an empty duration keeps the entry's duration. Extra time is
+    // added as a manual session after the entry's last one, missing time is cut off the latest ones
+    pub fn edit(
+        &mut self,
+        index: usize,
+        description_input: &str,
+        duration_input: &str,
+    ) -> std::result::Result<usize, OperationError> {
+        let index = self.entry_index(index)?;
+        let (description, tags) = parse_tags(description_input);
+        if description.is_empty() {
+            return Err(OperationError::EmptyDescription);
+        }
+        let current = self.tracked_times[index].duration();
+        let duration = if duration_input.trim().is_empty() {
+            current
+        } else {
+            timer::parse_length(duration_input)?
+        };
+
+        if duration < current {
+            self.cut_entry(index, current - duration);
+        } else if duration > current {
+            let added = duration - current;
+            let now = self.clock.now();
+            let tracked_time = &mut self.tracked_times[index];
+            let start = tracked_time
+                .intervals
+                .iter()
+                .map(|interval| interval.end)
+                .max()
+                .unwrap_or(now - added);
+            tracked_time.intervals.push(Interval::new(
+                start,
+                start + added,
+                IntervalSource::Manual,
+            ));
+        }
+
+        let tracked_time = &mut self.tracked_times[index];
+        tracked_time.description = description;
+        tracked_time.tags = tags;
+        tracked_time.modified_at = self.clock.now();
+        Ok(index)
+    }
+
+    // Sessions are given by the entry's index in the list of entries and their own index. A new
+    // session starts and ends now, its times are filled in afterwards
+    pub fn add_interval(&mut self, index: usize) {
+        let now = self.clock.now();
+        let tracked_time = &mut self.tracked_times[index];
+        tracked_time
+            .intervals
+            .push(Interval::new(now, now, IntervalSource::Manual));
+        tracked_time.modified_at = now;
+    }
+
+    pub fn set_interval(
+        &mut self,
+        index: usize,
+        interval: usize,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> std::result::Result<(), OperationError> {
+        if end < start {
+            return Err(OperationError::EndBeforeStart);
+        }
+        let tracked_time = &mut self.tracked_times[index];
+        tracked_time.intervals[interval].start = start;
+        tracked_time.intervals[interval].end = end;
+        tracked_time.modified_at = self.clock.now();
+        Ok(())
+    }
+
+    pub fn delete_interval(&mut self, index: usize, interval: usize) {
+        let tracked_time = &mut self.tracked_times[index];
+        tracked_time.intervals.remove(interval);
+        tracked_time.modified_at = self.clock.now();
+    }
+
+    // Finds an entry by its number in the list ("3"), its ID ("@12") or a part of its description
+    // and tags that matches only one entry, and returns the number as shown in the list
+    pub fn find_entry(&self, reference: &str) -> std::result::Result<usize, OperationError> {
+        let reference = reference.trim();
+        if let Ok(number) = reference.parse::<usize>() {
+            self.entry_index(number)?;
+            return Ok(number);
+        }
+        if let Some(id) = reference.strip_prefix('@') {
+            if let Ok(id) = id.trim().parse::<i64>() {
+                return self
+                    .tracked_times
+                    .iter()
+                    .position(|tracked_time| tracked_time.id == Some(id))
+                    .map(|index| index + 1)
+                    .ok_or(OperationError::NoSuchId(id));
+            }
+        }
+
+        let matches = self.matching_entries(reference);
+        let exact = matches
+            .iter()
+            .filter(|&&index| {
+                self.tracked_times[index].description.to_lowercase() == reference.to_lowercase()
+            })
+            .copied()
+            .collect::<Vec<usize>>();
+        match (matches.as_slice(), exact.as_slice()) {
+            ([], _) => Err(OperationError::NoMatchingEntry(reference.to_string())),
+            ([index], _) | (_, [index]) => Ok(*index + 1),
+            _ => {
+                let mut numbers = matches
+                    .iter()
+                    .map(|index| index + 1)
+                    .collect::<Vec<usize>>();
+                numbers.sort_unstable();
+                Err(OperationError::AmbiguousEntry(
+                    reference.to_string(),
+                    numbers,
+                ))
+            }
+        }
+    }
+
+    // Indices of the entries whose description and tags contain every word of the query. Entries
+    // whose description equals or starts with the query come first, then the most recent ones
+    pub fn matching_entries(&self, query: &str) -> Vec<usize> {
+        let query = query.trim().to_lowercase();
+        let words = query.split_whitespace().collect::<Vec<&str>>();
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = Vec::new();
+        for (index, tracked_time) in self.tracked_times.iter().enumerate() {
+            let description = tracked_time.description.to_lowercase();
+            let text = std::iter::once(description.clone())
+                .chain(
+                    tracked_time
+                        .tags
+                        .iter()
+                        .map(|tag| format!("#{}", tag.to_lowercase())),
+                )
+                .collect::<Vec<String>>()
+                .join(" ");
+            if !words.iter().all(|word| text.contains(word)) {
+                continue;
+            }
+            let rank = if description == query {
+                0
+            } else if description.starts_with(&query) {
+                1
+            } else {
+                2
+            };
+            matches.push((rank, index));
+        }
+        matches.sort_by_key(|&(rank, index)| (rank, std::cmp::Reverse(index)));
+        matches.into_iter().map(|(_, index)| index).collect()
+    }
+
+    // Converts an index as shown in the list
+    fn entry_index(&self, index: usize) -> std::result::Result<usize, OperationError> {
+        if index == 0 || index > self.tracked_times.len() {
+            return Err(OperationError::NoSuchEntry(index));
+        }
+        Ok(index - 1)
+    }
+
+    // Cuts the time off the latest sessions of the entry and returns the removed parts
+    fn take_from_entry(
+        &mut self,
+        index: usize,
+        time_input: &str,
+    ) -> std::result::Result<Vec<Interval>, OperationError> {
+        let duration = timer::parse_duration(time_input, self.tracked_times[index].duration())?;
+        Ok(self.cut_entry(index, duration))
+    }
+
+    fn cut_entry(&mut self, index: usize, duration: chrono::Duration) -> Vec<Interval> {
+        let now = self.clock.now();
+        let tracked_time = &mut self.tracked_times[index];
+        let mut remaining = duration;
+
+        let mut taken = Vec::new();
+        tracked_time
+            .intervals
+            .sort_by_key(|interval| std::cmp::Reverse(interval.end));
+        while remaining > chrono::Duration::zero() && !tracked_time.intervals.is_empty() {
+            let interval = &mut tracked_time.intervals[0];
+            if interval.duration() <= remaining {
+                remaining = remaining - interval.duration();
+                let mut interval = tracked_time.intervals.remove(0);
+                interval.id = None;
+                taken.push(interval);
+            } else {
+                let split = interval.end - remaining;
+                taken.push(Interval::new(split, interval.end, interval.source));
+                interval.end = split;
+                remaining = chrono::Duration::zero();
+            }
+        }
+        tracked_time
+            .intervals
+            .sort_by_key(|interval| interval.start);
+        tracked_time.modified_at = now;
+        taken
+    }
+
+    // The entries and timers at the given indices, to be stored together
+    pub fn changes(&mut self, tracked_times: &[usize], timers: &[usize]) -> Changes<'_> {
+        Changes {
+            tracked_times: self
+                .tracked_times
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| tracked_times.contains(i))
+                .map(|(_, tracked_time)| tracked_time)
+                .collect(),
+            timers: self
+                .timers
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| timers.contains(i))
+                .map(|(_, timer)| timer)
+                .collect(),
+            ..Changes::default()
+        }
+    }
+
+    pub fn project(&self, id: i64) -> Option<&Project> {
+        self.projects.iter().find(|project| project.id == id)
+    }
+
+    pub fn client(&self, id: i64) -> Option<&Client> {
+        self.clients.iter().find(|client| client.id == id)
+    }
+
+    // Keeps the entries of a deleted project without a project
+    pub fn unassign_project(&mut self, id: i64) {
+        for tracked_time in self.tracked_times.iter_mut() {
+            if tracked_time.project_id == Some(id) {
+                tracked_time.project_id = None;
+            }
+        }
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tracked_time in self.tracked_times.iter() {
+            for tag in tracked_time.tags.iter() {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+        tags.sort();
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::database::SqliteStorage;
+    use chrono::{Duration, TimeZone};
+
+    fn clock() -> Rc<ManualClock> {
+        Rc::new(ManualClock::new(
+            chrono::Utc.ymd(2021, 3, 1).and_hms(9, 0, 0),
+        ))
+    }
+
+    // A tracker whose timer ran for an hour
+    fn tracker(clock: &Rc<ManualClock>) -> Tracker {
+        let mut tracker = Tracker::empty(clock.clone());
+        tracker.start_stop(0);
+        clock.advance(Duration::hours(1));
+        tracker
+    }
+
+    fn new_entry(description: &str) -> Target {
+        Target::New {
+            description: description.to_string(),
+            project_id: None,
+        }
+    }
+
+    #[test]
+    fn starts_pauses_resumes_and_clears() {
+        let clock = clock();
+        let mut tracker = tracker(&clock);
+        tracker.start_stop(0);
+        clock.advance(Duration::minutes(30));
+        assert!(!tracker.is_any_timer_running());
+        assert_eq!(tracker.current_duration(0), Duration::hours(1));
+
+        tracker.start_stop(0);
+        clock.advance(Duration::minutes(15));
+        assert_eq!(tracker.current_duration(0), Duration::minutes(75));
+
+        tracker.clear(0);
+        assert_eq!(tracker.current_duration(0), Duration::zero());
+        assert!(tracker.is_any_timer_running());
+    }
+
+    #[test]
+    fn splits_time_off_the_timer() {
+        let clock = clock();
+        let started = clock.now();
+        let mut tracker = tracker(&clock);
+
+        let index = tracker.apply_operation(0, "30m", new_entry("Review #work"));
+        assert_eq!(index, Ok(0));
+        let tracked_time = &tracker.tracked_times[0];
+        assert_eq!(tracked_time.description, "Review");
+        assert_eq!(tracked_time.tags, vec![String::from("work")]);
+        assert_eq!(tracked_time.duration(), Duration::minutes(30));
+        assert_eq!(tracked_time.intervals[0].start, started);
+        assert_eq!(tracker.current_duration(0), Duration::minutes(30));
+
+        let index = tracker.apply_operation(0, "10m", Target::Existing(1));
+        assert_eq!(index, Ok(0));
+        let intervals = &tracker.tracked_times[0].intervals;
+        assert_eq!(intervals.len(), 2);
+        assert_eq!(intervals[1].start, intervals[0].end);
+        assert_eq!(tracker.tracked_times[0].duration(), Duration::minutes(40));
+        assert_eq!(tracker.current_duration(0), Duration::minutes(20));
+    }
+
+    #[test]
+    fn keeps_the_time_on_the_timer_when_a_split_fails() {
+        let clock = clock();
+        let mut tracker = tracker(&clock);
+
+        let result = tracker.apply_operation(0, "10m", new_entry("#meeting"));
+        assert_eq!(result, Err(OperationError::EmptyDescription));
+        let result = tracker.apply_operation(0, "2h", new_entry("Review"));
+        assert_eq!(
+            result,
+            Err(OperationError::Duration(DurationError::ExceedsAvailable(
+                Duration::hours(1)
+            )))
+        );
+        let result = tracker.apply_operation(0, "10m", Target::Existing(1));
+        assert_eq!(result, Err(OperationError::NoSuchEntry(1)));
+        assert!(tracker.tracked_times.is_empty());
+        assert_eq!(tracker.current_duration(0), Duration::hours(1));
+    }
+
+    #[test]
+    fn time_box_goes_along_with_all_of_the_time() {
+        let clock = clock();
+        let mut tracker = tracker(&clock);
+        assert_eq!(tracker.set_time_box(0, "45m"), Ok(()));
+
+        tracker
+            .apply_operation(0, "all", new_entry("Review"))
+            .unwrap();
+        assert_eq!(
+            tracker.tracked_times[0].planned,
+            Some(Duration::minutes(45))
+        );
+        assert_eq!(tracker.timers[0].time_box, None);
+    }
+
+    #[test]
+    fn moves_time_between_entries_and_back_to_the_timer() {
+        let clock = clock();
+        let mut tracker = tracker(&clock);
+        tracker
+            .apply_operation(0, "30m", new_entry("Review"))
+            .unwrap();
+        tracker
+            .apply_operation(0, "20m", new_entry("Meeting"))
+            .unwrap();
+
+        assert_eq!(tracker.transfer(1, 2, "10m"), Ok((0, 1)));
+        assert_eq!(tracker.tracked_times[0].duration(), Duration::minutes(20));
+        assert_eq!(tracker.tracked_times[1].duration(), Duration::minutes(30));
+        assert_eq!(tracker.transfer(1, 1, "5m"), Err(OperationError::SameEntry));
+
+        assert_eq!(tracker.return_to_timer(2, 0, "5m"), Ok(1));
+        assert_eq!(tracker.tracked_times[1].duration(), Duration::minutes(25));
+        assert_eq!(tracker.current_duration(0), Duration::minutes(15));
+    }
+
+    #[test]
+    fn edits_sessions_at_the_time_of_the_clock() {
+        let clock = clock();
+        let mut tracker = tracker(&clock);
+        tracker
+            .apply_operation(0, "30m", new_entry("Review"))
+            .unwrap();
+        clock.advance(Duration::minutes(5));
+        let now = clock.now();
+
+        tracker.add_interval(0);
+        let tracked_time = &tracker.tracked_times[0];
+        assert_eq!(tracked_time.intervals[1].start, now);
+        assert_eq!(tracked_time.intervals[1].end, now);
+        assert_eq!(tracked_time.modified_at, now);
+
+        clock.advance(Duration::minutes(5));
+        let end = now + Duration::minutes(15);
+        assert_eq!(
+            tracker.set_interval(0, 1, end, now),
+            Err(OperationError::EndBeforeStart)
+        );
+        assert_eq!(tracker.set_interval(0, 1, now, end), Ok(()));
+        assert_eq!(tracker.tracked_times[0].duration(), Duration::minutes(45));
+        assert_eq!(tracker.tracked_times[0].modified_at, clock.now());
+
+        tracker.delete_interval(0, 0);
+        assert_eq!(tracker.tracked_times[0].duration(), Duration::minutes(15));
+    }
+
+    #[test]
+    fn continues_after_a_restart() {
+        let path = std::env::temp_dir().join(format!(
+            "simple_time_tracker_tracker_test_{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let storage = SqliteStorage::new(path.clone());
+
+        // A new database starts out with a stored timer
+        let clock = clock();
+        let mut tracker = Tracker::load(&storage, clock.clone()).unwrap();
+        tracker.start_stop(0);
+        clock.advance(Duration::hours(1));
+        tracker
+            .apply_operation(0, "30m", new_entry("Review"))
+            .unwrap();
+        tracker.add_timer("Reading").unwrap();
+        tracker.start_stop(1);
+        clock.advance(Duration::minutes(10));
+        tracker.start_stop(1);
+        storage
+            .save_changes(tracker.changes(&[0], &[0, 1]))
+            .unwrap();
+
+        clock.advance(Duration::hours(2));
+        let restarted = Tracker::load(&storage, clock.clone()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(restarted.timers.len(), 2);
+        assert!(restarted.timers[0].is_running);
+        assert_eq!(restarted.current_duration(0), Duration::minutes(160));
+        assert!(!restarted.timers[1].is_running);
+        assert_eq!(restarted.current_duration(1), Duration::minutes(10));
+        assert_eq!(restarted.tracked_times.len(), 1);
+        assert_eq!(restarted.tracked_times[0].description, "Review");
+        assert_eq!(restarted.tracked_times[0].duration(), Duration::minutes(30));
+    }
+}